@@ -0,0 +1,39 @@
+-- PostgreSQL support starts at schema version 5; earlier versions only
+-- ever existed for SQLite.
+
+-- Every commit we've seen, independent of whether it was delivered
+CREATE TABLE commits (
+    commit_id INTEGER PRIMARY KEY,
+    repo TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    changeset TEXT NOT NULL,
+    created TEXT NOT NULL,
+    likes BIGINT NOT NULL DEFAULT 0,
+    dislikes BIGINT NOT NULL DEFAULT 0,
+    message TEXT NOT NULL,
+    message_key TEXT NOT NULL,
+    author TEXT NOT NULL,
+    avatar TEXT NOT NULL,
+    raw_json TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL
+);
+
+-- Delivery state of a commit for each destination
+CREATE TABLE deliveries (
+    commit_id INTEGER NOT NULL REFERENCES commits (commit_id) ON DELETE CASCADE,
+    destination TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    message_id TEXT,
+    response_code INTEGER,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    delivered_at TEXT,
+    PRIMARY KEY (commit_id, destination)
+);
+
+CREATE INDEX idx_commits_duplicates ON commits (author, message_key);
+CREATE INDEX idx_commits_first_seen ON commits (first_seen_at);
+CREATE INDEX idx_deliveries_status ON deliveries (destination, status);
+CREATE INDEX idx_deliveries_message ON deliveries (destination, message_id);
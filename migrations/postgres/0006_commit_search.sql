@@ -0,0 +1,8 @@
+-- Full-text index over stored commits, maintained by PostgreSQL
+ALTER TABLE commits ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', message), 'A')
+    || setweight(to_tsvector('simple', author), 'B')
+    || setweight(to_tsvector('simple', branch), 'C')
+) STORED;
+
+CREATE INDEX idx_commits_search ON commits USING GIN (search_vector);
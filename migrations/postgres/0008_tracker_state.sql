@@ -0,0 +1,6 @@
+-- Small named values the tracker keeps between runs
+CREATE TABLE tracker_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,49 @@
+-- Everyone who has committed, with totals that survive commit pruning
+CREATE TABLE authors (
+    name TEXT PRIMARY KEY,
+    avatar TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    first_commit_id INTEGER NOT NULL,
+    last_commit_id INTEGER NOT NULL,
+    commit_count BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE author_repos (
+    author TEXT NOT NULL REFERENCES authors (name) ON DELETE CASCADE,
+    repo TEXT NOT NULL,
+    commit_count BIGINT NOT NULL DEFAULT 0,
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    PRIMARY KEY (author, repo)
+);
+
+-- Every avatar an author has used, so changes can be traced
+CREATE TABLE author_avatars (
+    author TEXT NOT NULL REFERENCES authors (name) ON DELETE CASCADE,
+    avatar TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL,
+    PRIMARY KEY (author, avatar)
+);
+
+INSERT INTO authors (
+    name, avatar, first_seen_at, last_seen_at, first_commit_id, last_commit_id, commit_count
+)
+SELECT
+    author,
+    (SELECT latest.avatar FROM commits latest
+        WHERE latest.author = c.author ORDER BY latest.commit_id DESC LIMIT 1),
+    MIN(first_seen_at), MAX(first_seen_at), MIN(commit_id), MAX(commit_id), COUNT(*)
+FROM commits c
+GROUP BY author;
+
+INSERT INTO author_repos (author, repo, commit_count, first_seen_at, last_seen_at)
+SELECT author, repo, COUNT(*), MIN(first_seen_at), MAX(first_seen_at)
+FROM commits
+GROUP BY author, repo;
+
+INSERT INTO author_avatars (author, avatar, first_seen_at)
+SELECT author, avatar, MIN(first_seen_at)
+FROM commits
+WHERE avatar != ''
+GROUP BY author, avatar;
@@ -0,0 +1,2 @@
+-- When a commit held back in a burst is due to be posted
+ALTER TABLE deliveries ADD COLUMN deliver_at TEXT;
@@ -0,0 +1,38 @@
+-- Timestamps become TIMESTAMPTZ. Queries still exchange them as UTC text in
+-- the format of SQLite's datetime(), so these functions give PostgreSQL a
+-- datetime() that converts both ways and the same SQL runs on both engines.
+CREATE FUNCTION datetime(value TEXT) RETURNS TIMESTAMPTZ AS $$
+    SELECT value::timestamp AT TIME ZONE 'UTC'
+$$ LANGUAGE sql STABLE STRICT;
+
+CREATE FUNCTION datetime(value TIMESTAMPTZ) RETURNS TEXT AS $$
+    SELECT to_char(value AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+ALTER TABLE commits
+    ALTER COLUMN first_seen_at TYPE TIMESTAMPTZ USING datetime(first_seen_at);
+
+ALTER TABLE deliveries
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING datetime(created_at),
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING datetime(updated_at),
+    ALTER COLUMN delivered_at TYPE TIMESTAMPTZ USING datetime(delivered_at),
+    ALTER COLUMN deliver_at TYPE TIMESTAMPTZ USING datetime(deliver_at);
+
+ALTER TABLE leases
+    ALTER COLUMN acquired_at TYPE TIMESTAMPTZ USING datetime(acquired_at),
+    ALTER COLUMN renewed_at TYPE TIMESTAMPTZ USING datetime(renewed_at),
+    ALTER COLUMN expires_at TYPE TIMESTAMPTZ USING datetime(expires_at);
+
+ALTER TABLE tracker_state
+    ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING datetime(updated_at);
+
+ALTER TABLE authors
+    ALTER COLUMN first_seen_at TYPE TIMESTAMPTZ USING datetime(first_seen_at),
+    ALTER COLUMN last_seen_at TYPE TIMESTAMPTZ USING datetime(last_seen_at);
+
+ALTER TABLE author_repos
+    ALTER COLUMN first_seen_at TYPE TIMESTAMPTZ USING datetime(first_seen_at),
+    ALTER COLUMN last_seen_at TYPE TIMESTAMPTZ USING datetime(last_seen_at);
+
+ALTER TABLE author_avatars
+    ALTER COLUMN first_seen_at TYPE TIMESTAMPTZ USING datetime(first_seen_at);
@@ -0,0 +1,9 @@
+CREATE TABLE sent_commits (
+    id INTEGER PRIMARY KEY,
+    commit_id INTEGER NOT NULL UNIQUE,
+    author TEXT NOT NULL,
+    message TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    changeset TEXT NOT NULL,
+    sent_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,3 @@
+ALTER TABLE sent_commits ADD COLUMN repo TEXT NOT NULL DEFAULT '';
+ALTER TABLE sent_commits ADD COLUMN likes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE sent_commits ADD COLUMN dislikes INTEGER NOT NULL DEFAULT 0;
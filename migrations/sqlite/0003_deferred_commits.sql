@@ -0,0 +1,6 @@
+-- Commits held back during quiet hours, delivered later as a summary
+CREATE TABLE deferred_commits (
+    commit_id INTEGER PRIMARY KEY,
+    payload TEXT NOT NULL,
+    deferred_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
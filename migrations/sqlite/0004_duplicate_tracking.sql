@@ -0,0 +1,4 @@
+ALTER TABLE sent_commits ADD COLUMN message_key TEXT;
+ALTER TABLE sent_commits ADD COLUMN message_id TEXT;
+
+CREATE INDEX idx_sent_commits_duplicates ON sent_commits (author, message_key, sent_at);
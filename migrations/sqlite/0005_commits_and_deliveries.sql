@@ -0,0 +1,76 @@
+-- Every commit we've seen, independent of whether it was delivered
+CREATE TABLE commits (
+    commit_id INTEGER PRIMARY KEY,
+    repo TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    changeset TEXT NOT NULL,
+    created TEXT NOT NULL,
+    likes INTEGER NOT NULL DEFAULT 0,
+    dislikes INTEGER NOT NULL DEFAULT 0,
+    message TEXT NOT NULL,
+    message_key TEXT NOT NULL,
+    author TEXT NOT NULL,
+    avatar TEXT NOT NULL,
+    raw_json TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL
+);
+
+-- Delivery state of a commit for each destination
+CREATE TABLE deliveries (
+    commit_id INTEGER NOT NULL REFERENCES commits (commit_id) ON DELETE CASCADE,
+    destination TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    message_id TEXT,
+    response_code INTEGER,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    delivered_at TEXT,
+    PRIMARY KEY (commit_id, destination)
+);
+
+INSERT INTO commits (
+    commit_id, repo, branch, changeset, created, likes, dislikes, message, message_key,
+    author, avatar, raw_json, first_seen_at
+)
+SELECT
+    commit_id, repo, branch, changeset, '', likes, dislikes, message,
+    COALESCE(message_key, ''), author, '',
+    json_object(
+        'id', commit_id, 'repo', repo, 'branch', branch, 'changeset', changeset,
+        'created', '', 'likes', likes, 'dislikes', dislikes, 'message', message,
+        'user', json_object('name', author, 'avatar', '')
+    ),
+    COALESCE(sent_at, CURRENT_TIMESTAMP)
+FROM sent_commits;
+
+-- Deferred commits kept the full payload, so recover what sent_commits dropped
+UPDATE commits SET
+    raw_json = (SELECT payload FROM deferred_commits d WHERE d.commit_id = commits.commit_id),
+    created = (SELECT json_extract(payload, '$.created') FROM deferred_commits d WHERE d.commit_id = commits.commit_id),
+    avatar = (SELECT json_extract(payload, '$.user.avatar') FROM deferred_commits d WHERE d.commit_id = commits.commit_id)
+WHERE commit_id IN (SELECT commit_id FROM deferred_commits);
+
+INSERT INTO deliveries (
+    commit_id, destination, status, attempts, message_id, created_at, updated_at, delivered_at
+)
+SELECT
+    s.commit_id,
+    'discord',
+    CASE WHEN d.commit_id IS NULL THEN 'sent' ELSE 'deferred' END,
+    CASE WHEN d.commit_id IS NULL THEN 1 ELSE 0 END,
+    s.message_id,
+    COALESCE(s.sent_at, CURRENT_TIMESTAMP),
+    COALESCE(s.sent_at, CURRENT_TIMESTAMP),
+    CASE WHEN d.commit_id IS NULL THEN COALESCE(s.sent_at, CURRENT_TIMESTAMP) END
+FROM sent_commits s
+LEFT JOIN deferred_commits d ON d.commit_id = s.commit_id;
+
+DROP TABLE deferred_commits;
+DROP TABLE sent_commits;
+
+CREATE INDEX idx_commits_duplicates ON commits (author, message_key);
+CREATE INDEX idx_commits_first_seen ON commits (first_seen_at);
+CREATE INDEX idx_deliveries_status ON deliveries (destination, status);
+CREATE INDEX idx_deliveries_message ON deliveries (destination, message_id);
@@ -0,0 +1,28 @@
+-- Full-text index over stored commits, kept in sync by triggers
+CREATE VIRTUAL TABLE commits_fts USING fts5 (
+    message,
+    author,
+    branch,
+    content = 'commits',
+    content_rowid = 'commit_id',
+    tokenize = 'porter unicode61'
+);
+
+INSERT INTO commits_fts (commits_fts) VALUES ('rebuild');
+
+CREATE TRIGGER commits_fts_insert AFTER INSERT ON commits BEGIN
+    INSERT INTO commits_fts (rowid, message, author, branch)
+    VALUES (new.commit_id, new.message, new.author, new.branch);
+END;
+
+CREATE TRIGGER commits_fts_delete AFTER DELETE ON commits BEGIN
+    INSERT INTO commits_fts (commits_fts, rowid, message, author, branch)
+    VALUES ('delete', old.commit_id, old.message, old.author, old.branch);
+END;
+
+CREATE TRIGGER commits_fts_update AFTER UPDATE OF message, author, branch ON commits BEGIN
+    INSERT INTO commits_fts (commits_fts, rowid, message, author, branch)
+    VALUES ('delete', old.commit_id, old.message, old.author, old.branch);
+    INSERT INTO commits_fts (rowid, message, author, branch)
+    VALUES (new.commit_id, new.message, new.author, new.branch);
+END;
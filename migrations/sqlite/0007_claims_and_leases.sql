@@ -0,0 +1,11 @@
+-- Instance that claimed a delivery before posting it
+ALTER TABLE deliveries ADD COLUMN claimed_by TEXT;
+
+-- Named leases held by one tracker instance at a time
+CREATE TABLE leases (
+    name TEXT PRIMARY KEY,
+    holder TEXT NOT NULL,
+    acquired_at TEXT NOT NULL,
+    renewed_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
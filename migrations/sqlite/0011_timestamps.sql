@@ -0,0 +1,2 @@
+-- SQLite keeps timestamps as text, which its datetime() already converts;
+-- only PostgreSQL changes column types at this version.
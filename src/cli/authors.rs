@@ -0,0 +1,66 @@
+use crate::core::Config;
+use crate::services::Database;
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct AuthorsArgs {
+    /// Show one author's repositories and avatar history
+    pub name: Option<String>,
+    /// Maximum number of authors to list
+    #[arg(long, default_value_t = 20)]
+    pub limit: i64,
+}
+
+pub async fn run(config: Config, args: AuthorsArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+
+    let Some(name) = args.name else {
+        let authors = database.get_authors(args.limit).await?;
+        if authors.is_empty() {
+            println!("No authors recorded yet");
+            return Ok(());
+        }
+
+        for author in &authors {
+            println!(
+                "{:>6} commits  {}  (first seen {}, last seen {})",
+                author.commit_count, author.name, author.first_seen_at, author.last_seen_at
+            );
+        }
+        return Ok(());
+    };
+
+    let profile = database
+        .get_author_profile(&name)
+        .await?
+        .ok_or_else(|| format!("No author named '{}'", name))?;
+    let author = &profile.author;
+
+    println!("{}", author.name);
+    println!("    {} commits", author.commit_count);
+    println!(
+        "    First seen {} (#{})",
+        author.first_seen_at, author.first_commit_id
+    );
+    println!(
+        "    Last seen  {} (#{})",
+        author.last_seen_at, author.last_commit_id
+    );
+
+    println!();
+    println!("Repositories:");
+    for repo in &profile.repos {
+        println!(
+            "    {:>6}  {}  ({} to {})",
+            repo.commit_count, repo.repo, repo.first_seen_at, repo.last_seen_at
+        );
+    }
+
+    println!();
+    println!("Avatars:");
+    for avatar in &profile.avatars {
+        println!("    {}  {}", avatar.first_seen_at, avatar.avatar);
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::core::Config;
+use crate::services::{CommitScraper, Database};
+use clap::Args;
+use log::debug;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct BackfillArgs {
+    /// Number of feed pages to read, newest first
+    #[arg(long, default_value_t = 10)]
+    pub pages: u32,
+}
+
+/// Stores commits from older feed pages so search, digests and the author
+/// registry cover them. Nothing is posted: the tracker only posts commits
+/// newer than the last one it handled.
+pub async fn run(config: Config, args: BackfillArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+    let scraper = CommitScraper::new()?;
+
+    let before = database.get_sent_commits_count().await?;
+    let mut pages_read = 0;
+    for page in 1..=args.pages {
+        let results = scraper
+            .fetch_page(&config.monitoring.commits_url, page)
+            .await?;
+        let Some(oldest) = results.last().map(|r| r.commit.id) else {
+            break;
+        };
+
+        for result in &results {
+            database.record_commit(&result.commit).await?;
+        }
+        pages_read = page;
+        debug!("Backfilled page {}, oldest commit #{}", page, oldest);
+    }
+    let stored = database.get_sent_commits_count().await? - before;
+
+    println!(
+        "✅ Stored {} new commits from {} page(s) of the feed",
+        stored, pages_read
+    );
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::core::Config;
+use crate::services::{Database, backup};
+use clap::Args;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct BackupArgs {
+    /// Directory to write the snapshot to; defaults to `backup.directory`
+    #[arg(long)]
+    pub directory: Option<PathBuf>,
+    /// Number of snapshots to keep, 0 for all; defaults to `backup.keep`
+    #[arg(long)]
+    pub keep: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    /// Snapshot file created by the `backup` command
+    pub backup: PathBuf,
+}
+
+pub async fn backup(config: Config, args: BackupArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+
+    let directory = args
+        .directory
+        .unwrap_or_else(|| PathBuf::from(&config.backup.directory));
+    let keep = args.keep.unwrap_or(config.backup.keep);
+    let path =
+        backup::create_backup(&database, config.database.url.expose(), &directory, keep).await?;
+
+    println!("✅ Backed up database to {}", path.display());
+    Ok(())
+}
+
+pub async fn restore(config: Config, args: RestoreArgs) -> Result<(), Box<dyn Error>> {
+    let version = backup::restore_backup(config.database.url.expose(), &args.backup).await?;
+
+    println!(
+        "✅ Restored {} (schema version {})",
+        args.backup.display(),
+        version
+    );
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use crate::cli::Cli;
+use crate::core::{Config, ConfigOrigins, ConfigSource};
+use clap::{Args, Subcommand};
+use serde_json::{Map, Value, json};
+use std::error::Error;
+use std::str::FromStr;
+use toml_edit::{DocumentMut, Item, Table};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the path of the config file in use
+    Path,
+    /// Print the configuration as loaded, including environment overrides,
+    /// with secrets redacted
+    Print(PrintArgs),
+    /// Check every setting and list all problems with their location
+    Check,
+    /// Print a JSON Schema for the config file, for editor completion
+    Schema,
+}
+
+#[derive(Debug, Args)]
+pub struct PrintArgs {
+    /// Annotate every value with where it was set: the file, an environment
+    /// variable, a flag or the default
+    #[arg(long)]
+    pub effective: bool,
+    /// toml or json
+    #[arg(long, default_value = "toml")]
+    pub format: PrintFormat,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PrintFormat {
+    Toml,
+    Json,
+}
+
+impl FromStr for PrintFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "toml" => Ok(PrintFormat::Toml),
+            "json" => Ok(PrintFormat::Json),
+            _ => Err(format!("Unknown format '{}'. Expected toml or json", s)),
+        }
+    }
+}
+
+pub fn run(cli: &Cli, command: ConfigCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        ConfigCommand::Path => println!("{}", cli.config.display()),
+        ConfigCommand::Print(args) => print(cli, args)?,
+        ConfigCommand::Check => check(&cli.source())?,
+        ConfigCommand::Schema => println!(
+            "{}",
+            serde_json::to_string_pretty(&schemars::schema_for!(Config))?
+        ),
+    }
+
+    Ok(())
+}
+
+fn print(cli: &Cli, args: PrintArgs) -> Result<(), Box<dyn Error>> {
+    let (config, origins) = cli.source().read_with_origins()?;
+    let config = config.redacted();
+
+    match (args.format, args.effective) {
+        (PrintFormat::Toml, false) => print!("{}", toml::to_string_pretty(&config)?),
+        (PrintFormat::Json, false) => println!("{}", serde_json::to_string_pretty(&config)?),
+        (PrintFormat::Toml, true) => {
+            let mut document: DocumentMut = toml::to_string_pretty(&config)?.parse()?;
+            annotate_toml(document.as_table_mut(), "", &origins);
+            print!("{}", document);
+        }
+        (PrintFormat::Json, true) => {
+            let value = annotate_json(serde_json::to_value(&config)?, "", &origins);
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the config the same way the tracker does, without creating or
+/// upgrading the file or asking questions.
+fn check(source: &ConfigSource) -> Result<(), Box<dyn Error>> {
+    source.read()?;
+    println!("✅ {} is valid", source.path.display());
+    Ok(())
+}
+
+/// Adds a trailing `# origin` comment to every value.
+fn annotate_toml(table: &mut Table, prefix: &str, origins: &ConfigOrigins) {
+    for (key, item) in table.iter_mut() {
+        let path = join(prefix, key.get());
+        match item {
+            Item::Value(value) => {
+                value
+                    .decor_mut()
+                    .set_suffix(format!("  # {}", origin(origins, &path)));
+            }
+            Item::Table(table) => annotate_toml(table, &path, origins),
+            Item::ArrayOfTables(tables) => {
+                for (index, table) in tables.iter_mut().enumerate() {
+                    annotate_toml(table, &format!("{}[{}]", path, index), origins);
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+/// Replaces every value with `{ "value": ..., "source": ... }`. Arrays of
+/// tables are annotated per entry, other arrays as a whole.
+fn annotate_json(value: Value, path: &str, origins: &ConfigOrigins) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    let annotated = annotate_json(value, &join(path, &key), origins);
+                    (key, annotated)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(entries) if !entries.is_empty() && entries.iter().all(Value::is_object) => {
+            Value::Array(
+                entries
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, entry)| {
+                        annotate_json(entry, &format!("{}[{}]", path, index), origins)
+                    })
+                    .collect(),
+            )
+        }
+        value => json!({ "value": value, "source": origin(origins, path) }),
+    }
+}
+
+fn origin(origins: &ConfigOrigins, key: &str) -> String {
+    origins.origin(key).unwrap_or_else(|| "default".to_string())
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
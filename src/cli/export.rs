@@ -0,0 +1,54 @@
+use crate::cli::FilterArgs;
+use crate::core::Config;
+use crate::services::{Database, ExportFormat, export};
+use clap::Args;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// json, ndjson or csv; guessed from the output file name if omitted
+    #[arg(long)]
+    pub format: Option<ExportFormat>,
+    /// File to write to, gzipped if it ends in `.gz`; stdout if omitted
+    #[arg(short, long)]
+    pub output: Option<String>,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+pub async fn run(config: Config, args: ExportArgs) -> Result<(), Box<dyn Error>> {
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Json);
+
+    let database = Database::new(config.database.url.expose()).await?;
+    let records = database.export_commits(&args.filter.into()).await?;
+
+    match &args.output {
+        Some(path) => {
+            let file = BufWriter::new(File::create(path)?);
+            if path.ends_with(".gz") {
+                let mut encoder = GzEncoder::new(file, Compression::default());
+                export::write_records(&mut encoder, format, &records)?;
+                encoder.finish()?.flush()?;
+            } else {
+                let mut file = file;
+                export::write_records(&mut file, format, &records)?;
+                file.flush()?;
+            }
+            eprintln!("✅ Exported {} commits to {}", records.len(), path);
+        }
+        None => {
+            let mut stdout = io::stdout().lock();
+            export::write_records(&mut stdout, format, &records)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::core::Config;
+use crate::services::{CommitScraper, Database, gaps};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct GapsArgs {
+    /// Only look at commit IDs above this one
+    #[arg(long, default_value_t = 0)]
+    pub after: i32,
+    /// Check the gaps against the feed to tell missed commits from hidden ones
+    #[arg(long)]
+    pub check: bool,
+    /// Maximum number of gaps to list, newest first
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+pub async fn run(config: Config, args: GapsArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+
+    let mut gaps = database.find_commit_gaps(args.after).await?;
+    if gaps.is_empty() {
+        println!("No gaps in stored commit IDs");
+        return Ok(());
+    }
+
+    let total: i64 = gaps.iter().map(|gap| gap.size()).sum();
+    let gap_count = gaps.len();
+    gaps.reverse();
+    gaps.truncate(args.limit);
+
+    for gap in &gaps {
+        if gap.first == gap.last {
+            println!("#{}", gap.first);
+        } else {
+            println!("#{}-#{} ({} IDs)", gap.first, gap.last, gap.size());
+        }
+    }
+    println!();
+    println!("{} gap(s), {} missing ID(s)", gap_count, total);
+
+    if !args.check {
+        return Ok(());
+    }
+
+    let scraper = CommitScraper::new()?;
+    let scan = gaps::resolve_gaps(
+        &scraper,
+        &config.monitoring.commits_url,
+        &gaps,
+        config.gaps.max_pages,
+    )
+    .await?;
+
+    println!();
+    for result in &scan.missed {
+        let commit = &result.commit;
+        println!(
+            "missed #{} {}/{} by {}",
+            commit.id,
+            commit.repo,
+            commit.branch,
+            commit.author()
+        );
+        println!("    {}", commit.link(&config.monitoring.commits_url));
+    }
+    for gap in &scan.unresolved {
+        println!(
+            "unresolved #{}-#{} (older than {} pages)",
+            gap.first, gap.last, config.gaps.max_pages
+        );
+    }
+    println!(
+        "{} missed, {} hidden in the {} gap(s) checked",
+        scan.missed.len(),
+        scan.hidden,
+        gaps.len()
+    );
+    Ok(())
+}
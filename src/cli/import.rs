@@ -0,0 +1,43 @@
+use crate::core::Config;
+use crate::services::{Database, ExportFormat, export};
+use clap::Args;
+use flate2::read::GzDecoder;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Export file to read, gzipped if it ends in `.gz`; `-` reads stdin
+    pub input: String,
+    /// json, ndjson or csv; guessed from the file name if omitted
+    #[arg(long)]
+    pub format: Option<ExportFormat>,
+}
+
+pub async fn run(config: Config, args: ImportArgs) -> Result<(), Box<dyn Error>> {
+    let format = args
+        .format
+        .or_else(|| ExportFormat::from_path(&args.input))
+        .ok_or("Can't tell the format from the file name, pass --format")?;
+
+    let reader: Box<dyn BufRead> = if args.input == "-" {
+        Box::new(io::stdin().lock())
+    } else if args.input.ends_with(".gz") {
+        Box::new(BufReader::new(GzDecoder::new(File::open(&args.input)?)))
+    } else {
+        Box::new(BufReader::new(File::open(&args.input)?))
+    };
+    let records = export::read_records(reader, format)?;
+
+    let database = Database::new(config.database.url.expose()).await?;
+    let summary = database.import_commits(&records).await?;
+
+    println!(
+        "✅ Imported {} new commits and {} new deliveries ({} commits read)",
+        summary.commits,
+        summary.deliveries,
+        records.len()
+    );
+    Ok(())
+}
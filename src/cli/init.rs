@@ -0,0 +1,267 @@
+use crate::core::{Config, Secret, check_http_url, check_webhook_url, redact};
+use crate::services::database::Backend;
+use chrono_tz::Tz;
+use clap::Args;
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+#[derive(Debug, Args)]
+pub struct InitArgs {
+    /// Ask for each setting and check the answers instead of writing the
+    /// template
+    #[arg(long, short)]
+    pub wizard: bool,
+    /// Overwrite an existing config file
+    #[arg(long)]
+    pub force: bool,
+}
+
+pub async fn run(path: &Path, args: &InitArgs) -> Result<(), Box<dyn Error>> {
+    if path.exists() && !args.force {
+        return Err(format!(
+            "'{}' already exists; use --force to overwrite it",
+            path.display()
+        )
+        .into());
+    }
+
+    if !args.wizard {
+        Config::write_template(path)?;
+        println!("✅ Created '{}'", path.display());
+        println!("📝 Set discord.webhook_url before starting the tracker");
+        return Ok(());
+    }
+
+    if !io::stdin().is_terminal() {
+        return Err("The wizard needs an interactive terminal".into());
+    }
+
+    let config = wizard().await?;
+    config.validate()?;
+    std::fs::write(path, toml::to_string_pretty(&config)?)?;
+
+    println!();
+    println!("✅ Created '{}'", path.display());
+    println!("📝 Other settings keep their defaults; edit the file to change them");
+    Ok(())
+}
+
+/// Asks for the settings most installs change, checking each answer before
+/// moving on. Everything else keeps its default.
+async fn wizard() -> Result<Config, Box<dyn Error>> {
+    let mut config = Config::default();
+    let defaults = Config::default();
+
+    println!("🔧 Setting up rust-commit-tracker. Press Enter to keep a [default].");
+    println!();
+
+    loop {
+        let url = ask("Discord webhook URL", None, |answer| {
+            check_webhook_url(answer).map(|()| Secret::new(answer))
+        })?;
+        url.register();
+        match check_webhook_live(url.expose()).await {
+            Ok(name) => {
+                if let Some(name) = name {
+                    println!("   ✅ Found webhook '{}'", name);
+                }
+                config.discord.webhook_url = url;
+                break;
+            }
+            Err(e) => println!("   ❌ {}", redact(&e)),
+        }
+    }
+
+    config.discord.bot_name = ask("Bot name", Some(&defaults.discord.bot_name), |answer| {
+        Ok(answer.to_string())
+    })?;
+    config.discord.bot_avatar_url = ask(
+        "Bot avatar URL",
+        Some(&defaults.discord.bot_avatar_url),
+        |answer| check_http_url(answer).map(|()| answer.to_string()),
+    )?;
+
+    let lease_ttl = config.lease.ttl_secs;
+    config.monitoring.check_interval_secs = ask(
+        "Seconds between checks",
+        Some(&defaults.monitoring.check_interval_secs.to_string()),
+        |answer| match answer.parse::<u64>() {
+            Ok(0) | Err(_) => Err("Enter a whole number of seconds".to_string()),
+            Ok(secs) if secs >= lease_ttl => Err(format!(
+                "Must be shorter than the {} second lease",
+                lease_ttl
+            )),
+            Ok(secs) => Ok(secs),
+        },
+    )?;
+
+    config.database.url = ask(
+        "Database URL",
+        Some(defaults.database.url.expose()),
+        |answer| {
+            Backend::from_url(answer)
+                .map(|_| Secret::new(answer))
+                .map_err(|e| e.to_string())
+        },
+    )?;
+
+    config.digest.enabled = ask("Post daily and weekly digests? (y/n)", Some("n"), yes_no)?;
+    if config.digest.enabled {
+        config.digest.timezone = ask(
+            "Digest timezone",
+            Some(&defaults.digest.timezone),
+            |answer| {
+                answer
+                    .parse::<Tz>()
+                    .map(|_| answer.to_string())
+                    .map_err(|_| format!("Unknown timezone '{}'", answer))
+            },
+        )?;
+    }
+
+    Ok(config)
+}
+
+/// Prompts until `parse` accepts the answer. An empty answer means
+/// `default`, when there is one.
+fn ask<T>(
+    question: &str,
+    default: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<T, Box<dyn Error>> {
+    let stdin = io::stdin();
+    loop {
+        match default {
+            Some(default) => print!("{} [{}]: ", question, default),
+            None => print!("{}: ", question),
+        }
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            return Err("Setup cancelled".into());
+        }
+
+        let answer = match (answer.trim(), default) {
+            ("", Some(default)) => default,
+            ("", None) => continue,
+            (answer, _) => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("   ❌ {}", redact(&e)),
+        }
+    }
+}
+
+fn yes_no(answer: &str) -> Result<bool, String> {
+    match answer.to_ascii_lowercase().as_str() {
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        _ => Err("Answer y or n".to_string()),
+    }
+}
+
+/// Asks Discord about the webhook and returns its name. Network trouble
+/// isn't the URL's fault, so it only warns and returns `None`.
+async fn check_webhook_live(url: &str) -> Result<Option<String>, String> {
+    // Errors would otherwise repeat the URL, token and all
+    let response = match reqwest::get(url).await.map_err(reqwest::Error::without_url) {
+        Ok(response) => response,
+        Err(e) => {
+            println!(
+                "   ⚠️  Couldn't reach Discord to check the webhook: {}",
+                redact(&e.to_string())
+            );
+            return Ok(None);
+        }
+    };
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Discord doesn't accept this webhook (status {})",
+            response.status()
+        ));
+    }
+
+    let webhook: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+    Ok(Some(
+        webhook["name"].as_str().unwrap_or("unnamed").to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TEMPLATE: InitArgs = InitArgs {
+        wizard: false,
+        force: false,
+    };
+
+    #[tokio::test]
+    async fn writes_the_template_once_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        run(&path, &TEMPLATE).await.unwrap();
+        let template = fs::read_to_string(&path).unwrap();
+        let config: Config = toml::from_str(&template).unwrap();
+        assert_eq!(
+            config.discord.webhook_url.expose(),
+            Config::default().discord.webhook_url.expose()
+        );
+
+        fs::write(&path, "# Edited\n").unwrap();
+        let error = run(&path, &TEMPLATE).await.unwrap_err().to_string();
+        assert!(error.contains("--force"), "{}", error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Edited\n");
+
+        let force = InitArgs {
+            force: true,
+            ..TEMPLATE
+        };
+        run(&path, &force).await.unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), template);
+    }
+
+    #[test]
+    fn reads_yes_and_no_in_any_case() {
+        assert_eq!(yes_no("Y"), Ok(true));
+        assert_eq!(yes_no("no"), Ok(false));
+        assert!(yes_no("maybe").is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_the_webhook_with_discord() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "name": "Commits" })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        let url = format!("{}/api/webhooks/1/token", server.uri());
+
+        assert_eq!(
+            check_webhook_live(&url).await,
+            Ok(Some("Commits".to_string()))
+        );
+        let error = check_webhook_live(&url).await.unwrap_err();
+        assert!(error.contains("401"), "{}", error);
+
+        // Network trouble only warns
+        assert_eq!(check_webhook_live("http://127.0.0.1:9/").await, Ok(None));
+    }
+}
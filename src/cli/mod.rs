@@ -0,0 +1,182 @@
+pub mod authors;
+pub mod backfill;
+pub mod backup;
+pub mod config;
+pub mod export;
+pub mod gaps;
+pub mod import;
+pub mod init;
+pub mod replay;
+pub mod run;
+pub mod search;
+pub mod status;
+pub mod webhook;
+
+use crate::core::{CONFIG_FILE, Config, ConfigSource};
+use crate::models::CommitFilter;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use log::LevelFilter;
+use std::error::Error;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "rust-commit-tracker", version, about)]
+pub struct Cli {
+    /// Config file to use; created on first run if missing
+    #[arg(long, global = true, default_value = CONFIG_FILE)]
+    pub config: PathBuf,
+    /// Database URL, overriding `database.url` from the config
+    #[arg(long, global = true)]
+    pub database: Option<String>,
+    /// Profile to use when the config declares several; `run` starts all
+    /// of them unless one is given
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Log level: off, error, warn, info, debug or trace
+    #[arg(long, global = true, default_value_t = LevelFilter::Info)]
+    pub log_level: LevelFilter,
+    /// Never wait for input; a missing config file is written as a template
+    /// and the command fails. Implied when stdin isn't a terminal
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Whether the user can be asked questions, e.g. during first-run setup.
+    pub fn interactive(&self) -> bool {
+        !self.non_interactive && io::stdin().is_terminal()
+    }
+
+    /// The config file together with the command-line overrides.
+    pub fn source(&self) -> ConfigSource {
+        ConfigSource {
+            path: self.config.clone(),
+            database_url: self.database.clone(),
+            profile: self.profile.clone(),
+        }
+    }
+
+    pub fn load_config(&self) -> Result<Config, Box<dyn Error>> {
+        self.source().load(self.interactive())
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Monitor the commit feed and post new commits to Discord (default)
+    Run,
+    /// Write a config file, optionally answering questions for each setting
+    Init(init::InitArgs),
+    /// Check the feed once, post anything new and exit
+    Once,
+    /// Store older commits from the feed without posting them
+    Backfill(backfill::BackfillArgs),
+    /// Show what the database holds and which instance is active
+    Status,
+    /// Post stored commits to Discord again
+    Replay(replay::ReplayArgs),
+    /// Send a test message to the configured webhook
+    TestWebhook,
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(config::ConfigCommand),
+    /// Search stored commits by message, author or branch
+    Search(search::SearchArgs),
+    /// Export stored commits and their delivery state
+    Export(export::ExportArgs),
+    /// Merge commits from an export into the database
+    Import(import::ImportArgs),
+    /// Write a consistent snapshot of the SQLite database
+    Backup(backup::BackupArgs),
+    /// Replace the SQLite database with a snapshot; stop the tracker first
+    Restore(backup::RestoreArgs),
+    /// List holes in the stored commit IDs and optionally check them
+    Gaps(gaps::GapsArgs),
+    /// List commit authors or show one author's history
+    Authors(authors::AuthorsArgs),
+}
+
+/// Filters shared by commands that select stored commits.
+#[derive(Debug, Args)]
+pub struct FilterArgs {
+    /// Only commits to this repository
+    #[arg(long)]
+    pub repo: Option<String>,
+    /// Only commits to this branch
+    #[arg(long)]
+    pub branch: Option<String>,
+    /// Only commits by this author
+    #[arg(long)]
+    pub author: Option<String>,
+    /// Only commits made on or after this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub since: Option<NaiveDate>,
+    /// Only commits made on or before this date (YYYY-MM-DD)
+    #[arg(long)]
+    pub until: Option<NaiveDate>,
+}
+
+impl From<FilterArgs> for CommitFilter {
+    fn from(args: FilterArgs) -> Self {
+        Self {
+            repo: args.repo,
+            branch: args.branch,
+            author: args.author,
+            since: args.since,
+            until: args.until,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_global_options_after_the_subcommand() {
+        let cli = Cli::try_parse_from([
+            "rust-commit-tracker",
+            "status",
+            "--config",
+            "/etc/tracker.toml",
+            "--database",
+            "sqlite:status.db",
+            "--log-level",
+            "debug",
+            "--profile",
+            "staging",
+        ])
+        .unwrap();
+        assert!(matches!(cli.command, Some(Command::Status)));
+        assert_eq!(cli.log_level, LevelFilter::Debug);
+
+        let source = cli.source();
+        assert_eq!(source.path, PathBuf::from("/etc/tracker.toml"));
+        assert_eq!(source.database_url.as_deref(), Some("sqlite:status.db"));
+        assert_eq!(source.profile.as_deref(), Some("staging"));
+    }
+
+    #[test]
+    fn runs_with_the_defaults_without_a_subcommand() {
+        let cli = Cli::try_parse_from(["rust-commit-tracker"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.config, PathBuf::from(CONFIG_FILE));
+        assert_eq!(cli.log_level, LevelFilter::Info);
+        assert!(cli.database.is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_values() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(["rust-commit-tracker"].iter().chain(args)).is_err()
+        };
+        assert!(parse(&["--log-level", "loud"]));
+        assert!(parse(&["status", "--verbose"]));
+        assert!(parse(&["search", "door", "--since", "yesterday"]));
+        assert!(!parse(&["search", "door", "--since", "2025-01-01"]));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::core::Config;
+use crate::services::{CommitResult, Database, DiscordNotifier, WebhookError};
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// IDs of the stored commits to post
+    #[arg(required = true)]
+    pub commit_ids: Vec<i32>,
+}
+
+/// Posts stored commits again regardless of their delivery state, e.g. after
+/// a post was deleted. Quiet hours and duplicate grouping don't apply.
+pub async fn run(config: Config, args: ReplayArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+    let notifier = DiscordNotifier::new(config);
+    let destination = notifier.destination();
+
+    let records = database.get_commit_records(&args.commit_ids).await?;
+    for commit_id in &args.commit_ids {
+        if !records.iter().any(|r| r.commit.id == *commit_id) {
+            println!("⚠️  Commit #{} is not stored, skipping", commit_id);
+        }
+    }
+    if records.is_empty() {
+        return Err("None of the given commits are stored".into());
+    }
+
+    for record in records {
+        let result = CommitResult::stored(record.commit);
+        let commit_id = result.commit.id;
+
+        match notifier.send_commit_notification(&result, &[]).await {
+            Ok(sent) => {
+                database
+                    .mark_commit_sent(commit_id, destination, Some(&sent.id), Some(sent.status))
+                    .await?;
+                println!("✅ Posted commit #{} as message {}", commit_id, sent.id);
+            }
+            Err(e) => {
+                let status = e.downcast_ref::<WebhookError>().map(|e| e.status.as_u16());
+                database
+                    .record_delivery_failure(commit_id, destination, status, &e.to_string())
+                    .await?;
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(())
+}
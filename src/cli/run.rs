@@ -0,0 +1,85 @@
+use crate::core::{CommitTracker, Config, ConfigSource, ConfigWatcher, in_profile};
+use crate::services::CommitScraper;
+use log::{error, info};
+use std::error::Error;
+use std::time::Duration;
+use tokio::task::{JoinSet, LocalSet};
+
+pub async fn run(config: Config, source: ConfigSource) -> Result<(), Box<dyn Error>> {
+    if config.profiles.is_empty() {
+        let mut tracker = CommitTracker::new(config)
+            .await?
+            .with_watcher(ConfigWatcher::new(source));
+        return tracker.start().await;
+    }
+
+    let profiles = config.resolve_profiles()?;
+    info!(
+        "🚀 Starting {} profiles: {}",
+        profiles.len(),
+        config.profile_names().join(", ")
+    );
+
+    // Profiles polling the same feed share a response fetched within half
+    // of the shortest interval
+    let shortest = profiles
+        .iter()
+        .map(|(_, profile)| profile.monitoring.check_interval_secs)
+        .min()
+        .unwrap_or_default();
+    let scraper = CommitScraper::new()?.sharing_responses(Duration::from_secs(shortest) / 2);
+
+    // Trackers aren't Send, so they share this thread
+    let local = LocalSet::new();
+    let shut_down = local
+        .run_until(async move {
+            let mut tasks = JoinSet::new();
+            for (name, profile) in profiles {
+                let source = source.for_profile(&name);
+                let scraper = scraper.clone();
+                tasks.spawn_local(in_profile(name, async move {
+                    match run_profile(profile, source, scraper).await {
+                        Ok(()) => true,
+                        Err(e) => {
+                            error!("❌ Tracker stopped: {}", e);
+                            false
+                        }
+                    }
+                }));
+            }
+
+            // A failing profile leaves the others running. Trackers only
+            // stop cleanly when the process is asked to shut down.
+            let mut shut_down = false;
+            while let Some(result) = tasks.join_next().await {
+                match result {
+                    Ok(stopped_cleanly) => shut_down |= stopped_cleanly,
+                    Err(e) => error!("❌ Tracker task panicked: {}", e),
+                }
+            }
+            shut_down
+        })
+        .await;
+
+    if shut_down {
+        Ok(())
+    } else {
+        Err("Every profile's tracker has stopped".into())
+    }
+}
+
+async fn run_profile(
+    config: Config,
+    source: ConfigSource,
+    scraper: CommitScraper,
+) -> Result<(), Box<dyn Error>> {
+    let mut tracker = CommitTracker::with_scraper(config, scraper)
+        .await?
+        .with_watcher(ConfigWatcher::new(source));
+    tracker.start().await
+}
+
+pub async fn once(config: Config) -> Result<(), Box<dyn Error>> {
+    let mut tracker = CommitTracker::new(config).await?;
+    tracker.run_once().await
+}
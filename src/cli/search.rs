@@ -0,0 +1,49 @@
+use crate::cli::FilterArgs;
+use crate::core::Config;
+use crate::models::SearchQuery;
+use crate::services::Database;
+use clap::Args;
+use std::error::Error;
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Words to search for; append `*` to a word for prefix matches
+    #[arg(required = true)]
+    pub query: Vec<String>,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    /// Maximum number of results
+    #[arg(long, default_value_t = 20)]
+    pub limit: i64,
+}
+
+pub async fn run(config: Config, args: SearchArgs) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+
+    let hits = database
+        .search_commits(&SearchQuery {
+            text: args.query.join(" "),
+            filter: args.filter.into(),
+            limit: args.limit,
+        })
+        .await?;
+
+    if hits.is_empty() {
+        println!("No matching commits found");
+        return Ok(());
+    }
+
+    for hit in &hits {
+        let date = hit.created.get(..10).unwrap_or(&hit.created);
+        println!(
+            "#{} {} {}/{} by {}",
+            hit.commit_id, date, hit.repo, hit.branch, hit.author
+        );
+        println!("    {}", hit.snippet.replace('\n', " "));
+        println!("    {}", hit.link(&config.monitoring.commits_url));
+    }
+
+    println!();
+    println!("{} result(s)", hits.len());
+    Ok(())
+}
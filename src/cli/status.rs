@@ -0,0 +1,48 @@
+use crate::core::{Config, TRACKER_LEASE};
+use crate::services::Database;
+use std::error::Error;
+
+pub async fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let database = Database::new(config.database.url.expose()).await?;
+
+    println!(
+        "Database:         {}, schema version {}",
+        database.backend().name(),
+        database.schema_version().await?
+    );
+    println!(
+        "Commits stored:   {}",
+        database.get_sent_commits_count().await?
+    );
+
+    match database.get_latest_commit_id().await? {
+        Some(latest_id) => println!("Latest commit:    #{}", latest_id),
+        None => println!("Latest commit:    none"),
+    }
+    match database.get_last_sent_commit_info().await? {
+        Some((last_id, changeset)) => println!("Last handled:     #{} ({})", last_id, changeset),
+        None => println!("Last handled:     none"),
+    }
+
+    let counts = database.get_delivery_counts().await?;
+    if counts.is_empty() {
+        println!("Deliveries:       none");
+    } else {
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(status, count)| format!("{} {}", count, status))
+            .collect();
+        println!("Deliveries:       {}", counts.join(", "));
+    }
+
+    if config.lease.enabled {
+        match database.lease_holder(TRACKER_LEASE).await? {
+            Some(holder) => println!("Active instance:  {}", holder),
+            None => println!("Active instance:  none"),
+        }
+    } else {
+        println!("Active instance:  lease disabled");
+    }
+
+    Ok(())
+}
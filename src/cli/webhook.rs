@@ -0,0 +1,14 @@
+use crate::core::Config;
+use crate::services::DiscordNotifier;
+use std::error::Error;
+
+pub async fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let notifier = DiscordNotifier::new(config);
+    let sent = notifier.send_test_message().await?;
+
+    println!(
+        "✅ Webhook works: Discord answered {} with message {}",
+        sent.status, sent.id
+    );
+    Ok(())
+}
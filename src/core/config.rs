@@ -1,105 +1,849 @@
+use crate::core::{
+    CONFIG_VERSION, ConfigErrors, ConfigOrigins, Migration, Problems, QuietWindow, REDACTED,
+    Schedule, Secret, apply_env_overrides, check_profile_name, env_var_name, migrate_config,
+    overlay, profile_database_url, sets_key, unset_key,
+};
+use crate::services::DESTINATIONS;
+use crate::services::database::Backend;
+use chrono_tz::Tz;
+use log::info;
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
 
-const CONFIG_FILE: &str = "config.toml";
+/// Keys holding secrets, which are never printed.
+pub const SECRET_KEYS: &[&str] = &["discord.webhook_url", "database.url"];
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Secrets paired with the key naming a file to read them from.
+const SECRET_FILE_KEYS: &[(&str, &str)] = &[
+    ("discord.webhook_url", "discord.webhook_url_file"),
+    ("database.url", "database.url_file"),
+];
+
+/// Config file used when no `--config` path is given.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Settings for rust-commit-tracker, read from `config.toml`. Keys missing
+/// from the file are filled in with their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
 pub struct Config {
+    /// Format version of the file, used to upgrade older files on load
+    pub config_version: u32,
+    /// Where and how commits are posted
     pub discord: DiscordConfig,
+    /// Where commits are read from
     pub monitoring: MonitoringConfig,
+    /// Look of the posted embeds
     pub appearance: AppearanceConfig,
+    /// Where commits and deliveries are stored
     pub database: DatabaseConfig,
+    /// Daily and weekly summary posts
+    pub digest: DigestConfig,
+    /// One post for a change landing on several branches
+    pub duplicates: DuplicatesConfig,
+    /// One post for a quick series of commits by one author
+    pub bursts: BurstsConfig,
+    /// Scheduled snapshots of the SQLite database
+    pub backup: BackupConfig,
+    /// Only one of several instances sharing a database polls and posts
+    pub lease: LeaseConfig,
+    /// Recovery of commits missed during outages
+    pub gaps: GapsConfig,
+    /// Tracking and welcoming of commit authors
+    pub authors: AuthorsConfig,
+    /// Named trackers run side by side in one process. Each profile sets
+    /// only what differs from the settings above; without profiles, the
+    /// settings above are the one tracker.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(with = "BTreeMap<String, Config>")]
+    pub profiles: BTreeMap<String, toml::Table>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where and how commits are posted.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
 pub struct DiscordConfig {
-    pub webhook_url: String,
+    /// Discord webhook URL, from Server Settings → Integrations → Webhooks
+    #[schemars(with = "String")]
+    pub webhook_url: Secret,
+    /// File holding the webhook URL, e.g. a Docker secret; replaces
+    /// `webhook_url` when set
+    #[serde(default)]
+    pub webhook_url_file: String,
+    /// Name the webhook posts under
     pub bot_name: String,
+    /// Avatar the webhook posts with
     pub bot_avatar_url: String,
+    /// Role and user pings for matching commits
+    #[serde(default)]
+    pub mentions: Vec<MentionRule>,
+    /// Windows during which commits are held back
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHours>,
+}
+
+/// A nightly window during which commits are held back and later delivered
+/// as a single summary. Times are `HH:MM` in the given IANA timezone.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QuietHours {
+    /// Start of the window, `HH:MM`
+    pub start: String,
+    /// End of the window, `HH:MM`; may be earlier than `start` to span midnight
+    pub end: String,
+    /// IANA timezone, e.g. `Europe/London`
+    pub timezone: String,
+    /// Destination the window holds back, e.g. `discord`; every destination
+    /// when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+}
+
+impl QuietHours {
+    pub fn window(&self) -> Result<QuietWindow, String> {
+        QuietWindow::parse(&self.start, &self.end, &self.timezone)
+    }
+
+    /// Whether the window holds back deliveries to `destination`.
+    pub fn applies_to(&self, destination: &str) -> bool {
+        self.destination.as_deref().is_none_or(|d| d == destination)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Pings roles and users when a commit matches every pattern set on the rule.
+/// Patterns are regular expressions; unset patterns are ignored, and a rule
+/// without any pattern never matches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct MentionRule {
+    /// Pattern matched against the commit message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Pattern matched against the repository name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Pattern matched against the branch name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Pattern matched against the author's name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Discord role IDs to ping
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Discord user IDs to ping
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+impl MentionRule {
+    /// Returns the configured patterns paired with the field they apply to.
+    pub fn patterns(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("message", &self.message),
+            ("repo", &self.repo),
+            ("branch", &self.branch),
+            ("author", &self.author),
+        ]
+        .into_iter()
+        .filter_map(|(field, pattern)| pattern.as_deref().map(|p| (field, p)))
+    }
+}
+
+/// Where commits are read from.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
 pub struct MonitoringConfig {
+    /// JSON feed of commits
     pub commits_url: String,
+    /// Seconds between checks of the feed
     pub check_interval_secs: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Look of the posted embeds.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
 pub struct AppearanceConfig {
+    /// Embed color as `#RRGGBB`
     pub embed_color: String,
+    /// Icon shown next to the embed footer
     pub footer_icon_url: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where commits and deliveries are stored.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
 pub struct DatabaseConfig {
-    pub url: String,
-    pub cleanup_keep_last: i64,
+    /// `sqlite:` file or `postgres://` URL
+    #[schemars(with = "String")]
+    pub url: Secret,
+    /// File holding the database URL; replaces `url` when set
+    #[serde(default)]
+    pub url_file: String,
+    /// When stored commits are pruned
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
-impl Config {
-    pub fn load_or_create() -> Result<Self, Box<dyn Error>> {
-        if Path::new(CONFIG_FILE).exists() {
-            let config = Self::load_from_file()?;
-            config.validate()?;
-            Ok(config)
+/// Pruning rules. A commit is pruned as soon as any rule drops it. Commits
+/// still waiting for delivery and the newest handled commit are always kept.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Keep only this many of the newest commits; 0 disables
+    pub keep_last: i64,
+    /// Prune commits first seen more than this many days ago; 0 disables
+    pub max_age_days: u64,
+    /// Directory to write pruned commits to as gzipped NDJSON before they're
+    /// deleted; empty deletes without archiving
+    pub archive_dir: String,
+    /// Separate limits per repository and/or branch
+    pub limits: Vec<RetentionLimit>,
+}
+
+/// Keeps only the newest `keep_last` commits matching `repo` and `branch`.
+/// Unset filters match everything.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RetentionLimit {
+    /// Only commits to this repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Only commits to this branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Number of matching commits to keep
+    pub keep_last: i64,
+}
+
+/// Periodic summary posts. Schedules are five-field cron expressions
+/// (`minute hour day month weekday`) evaluated in `timezone`; an empty
+/// schedule disables that digest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct DigestConfig {
+    /// Post digests
+    pub enabled: bool,
+    /// When the daily digest is posted; empty disables it
+    pub daily_schedule: String,
+    /// When the weekly digest is posted; empty disables it
+    pub weekly_schedule: String,
+    /// IANA timezone the schedules are evaluated in
+    pub timezone: String,
+    /// Number of most active authors listed
+    pub top_authors: usize,
+    /// Number of commits listed
+    pub top_commits: usize,
+}
+
+/// Collapses the same change landing on several branches into one post.
+/// Commits count as duplicates when author and normalized message match
+/// and they were made within `window_secs` of each other. Later duplicates
+/// either edit the original post to list the new branch, or are dropped
+/// when `edit_original` is off.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct DuplicatesConfig {
+    /// Collapse duplicates
+    pub enabled: bool,
+    /// Seconds between commits for them to count as duplicates
+    pub window_secs: u64,
+    /// Add the new branch to the original post instead of dropping the
+    /// duplicate
+    pub edit_original: bool,
+}
+
+/// Holds back commits from the same author on the same branch for
+/// `window_secs` after the first one, then posts them together.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct BurstsConfig {
+    /// Group bursts
+    pub enabled: bool,
+    /// Seconds to hold commits back after the first one of a burst
+    pub window_secs: u64,
+}
+
+/// Scheduled snapshots of the SQLite database. `schedule` is a five-field
+/// cron expression evaluated in `timezone`. Only the newest `keep` snapshots
+/// in `directory` are kept; 0 keeps all of them.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct BackupConfig {
+    /// Take scheduled snapshots
+    pub enabled: bool,
+    /// When snapshots are taken
+    pub schedule: String,
+    /// IANA timezone the schedule is evaluated in
+    pub timezone: String,
+    /// Directory the snapshots are written to
+    pub directory: String,
+    /// Number of snapshots kept; 0 keeps all of them
+    pub keep: usize,
+}
+
+/// Lets several instances share one database while only one of them polls
+/// and posts. The active instance renews its lease every poll; if it stops
+/// for `ttl_secs`, another instance takes over. An empty `instance_id` is
+/// derived from the host name and process ID.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct LeaseConfig {
+    /// Take the lease before polling
+    pub enabled: bool,
+    /// Seconds without renewal after which another instance takes over
+    pub ttl_secs: u64,
+    /// Name of this instance; derived from the host name and process ID
+    /// when empty
+    pub instance_id: String,
+}
+
+/// Looks for holes in the stored commit IDs every `interval_secs` and pages
+/// back through at most `max_pages` of the feed to find commits missed
+/// during outages.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct GapsConfig {
+    /// Look for gaps
+    pub enabled: bool,
+    /// Seconds between checks for gaps
+    pub interval_secs: u64,
+    /// Most feed pages read back per check
+    pub max_pages: u32,
+    /// What to do with recovered commits
+    pub action: GapAction,
+}
+
+/// Every author is tracked in the database; `announce_new` also posts a
+/// welcome the first time someone commits.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(default)]
+pub struct AuthorsConfig {
+    /// Welcome authors on their first commit
+    pub announce_new: bool,
+}
+
+/// What to do with recovered commits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GapAction {
+    /// Post them like any new commit
+    Deliver,
+    /// Only log them and store them as skipped
+    Report,
+}
+
+impl LeaseConfig {
+    pub fn instance_id(&self) -> String {
+        if !self.instance_id.trim().is_empty() {
+            return self.instance_id.trim().to_string();
+        }
+
+        let host = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .unwrap_or_else(|_| "tracker".to_string());
+        format!("{}-{}", host, std::process::id())
+    }
+}
+
+impl BackupConfig {
+    pub fn timezone(&self) -> Result<Tz, String> {
+        self.timezone
+            .parse::<Tz>()
+            .map_err(|_| format!("Unknown timezone '{}'", self.timezone))
+    }
+}
+
+impl DigestConfig {
+    pub fn timezone(&self) -> Result<Tz, String> {
+        self.timezone
+            .parse::<Tz>()
+            .map_err(|_| format!("Unknown timezone '{}'", self.timezone))
+    }
+}
+
+/// A missing config file was replaced with a template that still needs
+/// editing. Headless runs exit with `EXIT_CODE` instead of waiting for input.
+#[derive(Debug)]
+pub struct ConfigCreated {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl ConfigCreated {
+    /// `EX_CONFIG` from sysexits.h
+    pub const EXIT_CODE: i32 = 78;
+}
+
+impl fmt::Display for ConfigCreated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Created a config template at '{}', but it can't be used yet:\n{}\n\
+            Edit it or set RCT_* environment variables, then start again",
+            self.path.display(),
+            self.reason
+        )
+    }
+}
+
+impl Error for ConfigCreated {}
+
+/// An outdated config file brought up to date.
+struct Upgrade {
+    /// `config_version` of the file before the upgrade
+    version: i64,
+    migration: Migration,
+    /// The upgraded file, comments and all
+    content: String,
+}
+
+/// `<file>.v<version>.bak`, numbered if an earlier backup already took the
+/// name.
+fn backup_path(path: &Path, version: i64) -> PathBuf {
+    (0..)
+        .map(|n| {
+            let mut backup = path.as_os_str().to_owned();
+            if n == 0 {
+                backup.push(format!(".v{}.bak", version));
+            } else {
+                backup.push(format!(".v{}.{}.bak", version, n));
+            }
+            PathBuf::from(backup)
+        })
+        .find(|backup| !backup.exists())
+        .expect("some backup name is free")
+}
+
+/// Where the config comes from: a file plus command-line overrides. Kept so
+/// a reload reads the config the same way as startup.
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    /// Replaces `database.url` from the file
+    pub database_url: Option<String>,
+    /// Profile to load instead of the whole config
+    pub profile: Option<String>,
+}
+
+impl ConfigSource {
+    /// The same source narrowed to one profile.
+    pub fn for_profile(&self, name: &str) -> Self {
+        Self {
+            profile: Some(name.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Loads the config file, creating it first if it's missing, and layers
+    /// environment and command-line overrides over it. Without
+    /// `interactive` a new file is only a template: the tracker starts if
+    /// the overrides complete it and fails with `ConfigCreated` otherwise.
+    /// With a profile selected, returns that profile's settings.
+    pub fn load(&self, interactive: bool) -> Result<Config, Box<dyn Error>> {
+        self.load_with_origins(interactive)
+            .map(|(config, _)| config)
+    }
+
+    /// Like `load`, also returning where each value was set.
+    pub fn load_with_origins(
+        &self,
+        interactive: bool,
+    ) -> Result<(Config, ConfigOrigins), Box<dyn Error>> {
+        let path = &self.path;
+        let (config, origins, created) = if path.exists() {
+            let (config, origins) = Config::load_from_file(path)?;
+            (config, origins, false)
+        } else if interactive {
+            let (config, origins) = Config::create_default_and_prompt(path)?;
+            (config, origins, false)
         } else {
-            Self::create_default_and_prompt()
-        }
-    }
-
-    fn load_from_file() -> Result<Self, Box<dyn Error>> {
-        let content = fs::read_to_string(CONFIG_FILE)?;
-
-        // Try to parse the config, but handle missing fields gracefully
-        match toml::from_str::<Config>(&content) {
-            Ok(config) => Ok(config),
-            Err(e) => {
-                // If parsing fails due to missing fields, merge with defaults
-                if e.to_string().contains("missing field") {
-                    println!("⚠️  Config file is missing new fields, updating...");
-
-                    // Parse as a generic value first
-                    let mut existing: toml::Value = toml::from_str(&content)?;
-                    let default_config = Self::default();
-                    let default_value = toml::Value::try_from(&default_config)?;
-
-                    // Merge missing fields from defaults
-                    if let (toml::Value::Table(existing_table), toml::Value::Table(default_table)) =
-                        (&mut existing, default_value)
-                    {
-                        for (key, value) in default_table {
-                            if !existing_table.contains_key(&key) {
-                                existing_table.insert(key, value);
-                            }
-                        }
+            Config::write_template(path)?;
+            info!("📝 Created config template at '{}'", path.display());
+            (Config::default(), ConfigOrigins::new(path), true)
+        };
+        self.resolve(config, origins, created)
+    }
+
+    /// Like `load`, but never creates, prompts for or writes the file. An
+    /// outdated file is upgraded in memory only.
+    pub fn read(&self) -> Result<Config, Box<dyn Error>> {
+        self.read_with_origins().map(|(config, _)| config)
+    }
+
+    /// Like `read`, also returning where each value was set.
+    pub fn read_with_origins(&self) -> Result<(Config, ConfigOrigins), Box<dyn Error>> {
+        if !self.path.exists() {
+            return Err(format!(
+                "'{}' doesn't exist; run `init` to create it",
+                self.path.display()
+            )
+            .into());
+        }
+        let (config, origins, _) = Config::read_file(&self.path)?;
+        self.resolve(config, origins, false)
+    }
+
+    /// Layers the overrides over the config from the file, validates it and
+    /// picks the selected profile.
+    fn resolve(
+        &self,
+        config: Config,
+        mut origins: ConfigOrigins,
+        created: bool,
+    ) -> Result<(Config, ConfigOrigins), Box<dyn Error>> {
+        let path = &self.path;
+        let mut problems = Problems::default();
+        let (mut config, applied) = config.with_env_overrides(std::env::vars(), &mut problems)?;
+        for key in applied {
+            let source = env_var_name(&key);
+            origins.set(key, source);
+        }
+
+        for (key, source) in config.read_secret_files()? {
+            origins.set(key, source);
+        }
+        if let Some(url) = &self.database_url {
+            if !config.profiles.is_empty() {
+                return Err("--database can't be used with profiles; \
+                    set database.url in each profile instead"
+                    .into());
+            }
+            config.database.url = Secret::new(url.as_str());
+            origins.set("database.url", "--database");
+        }
+
+        config.register_secrets();
+        let profiles = if config.profiles.is_empty() {
+            config.validate().map(|()| Vec::new())
+        } else {
+            config.resolve_profiles()
+        };
+        let profiles = match problems.finish_with(profiles) {
+            Ok(profiles) => profiles,
+            Err(e) if created => {
+                return Err(ConfigCreated {
+                    path: path.to_path_buf(),
+                    reason: e.locate(&origins).to_string(),
+                }
+                .into());
+            }
+            Err(e) => return Err(e.locate(&origins).into()),
+        };
+        for (_, profile) in &profiles {
+            profile.register_secrets();
+        }
+
+        let Some(name) = &self.profile else {
+            return Ok((config, origins));
+        };
+        match profiles.into_iter().find(|(profile, _)| profile == name) {
+            Some((_, profile)) => {
+                if profile.database.url != config.database.url
+                    && !sets_key(&config.profiles[name], "database")
+                {
+                    origins.set(
+                        format!("profiles.{}.database.url", name),
+                        "derived from database.url",
+                    );
+                }
+                Ok((profile, origins.for_profile(name)))
+            }
+            None if config.profiles.is_empty() => {
+                Err(format!("No profile '{}'; the config declares no profiles", name).into())
+            }
+            None => Err(format!(
+                "No profile '{}'; expected one of {}",
+                name,
+                config.profile_names().join(", ")
+            )
+            .into()),
+        }
+    }
+}
+
+impl Config {
+    /// Replaces secrets that have a `*_file` key set with the file's
+    /// contents and returns the keys replaced with the file they came from.
+    /// The files are read on every load and never written back.
+    fn read_secret_files(&mut self) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+        let mut read = Vec::new();
+        for (secret_key, key, file, secret) in [
+            (
+                "discord.webhook_url",
+                "discord.webhook_url_file",
+                &self.discord.webhook_url_file,
+                &mut self.discord.webhook_url,
+            ),
+            (
+                "database.url",
+                "database.url_file",
+                &self.database.url_file,
+                &mut self.database.url,
+            ),
+        ] {
+            if file.trim().is_empty() {
+                continue;
+            }
+
+            let value = fs::read_to_string(file)
+                .map_err(|e| format!("❌ Can't read {} '{}': {}", key, file, e))?;
+            *secret = Secret::new(value.trim());
+            read.push((secret_key, format!("{} ({})", key, file)));
+        }
+
+        Ok(read)
+    }
+
+    /// Lets `redact` scrub the configured secrets from logs and errors.
+    /// Defaults and the webhook placeholder stay readable.
+    fn register_secrets(&self) {
+        let defaults = Self::default();
+        for (secret, default) in [
+            (&self.discord.webhook_url, &defaults.discord.webhook_url),
+            (&self.database.url, &defaults.database.url),
+        ] {
+            if secret != default {
+                secret.register();
+            }
+        }
+    }
+
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// The settings for one profile: the shared settings with the
+    /// profile's own layered over them. A profile that doesn't set a
+    /// database gets its own SQLite file next to the shared one.
+    pub fn profile(&self, name: &str) -> Result<Config, String> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("No profile '{}'", name))?;
+        check_profile_name(name)?;
+
+        let mut shared = self.clone();
+        shared.profiles.clear();
+        let mut merged = toml::Table::try_from(&shared).map_err(|e| e.to_string())?;
+
+        // A secret set in the profile replaces the file the shared one is
+        // read from
+        for (secret_key, file_key) in SECRET_FILE_KEYS {
+            if sets_key(profile, secret_key) && !sets_key(profile, file_key) {
+                set_value(&mut merged, file_key, toml::Value::String(String::new()));
+            }
+        }
+        if !sets_key(profile, "database.url")
+            && !sets_key(profile, "database.url_file")
+            && let Some(url) = profile_database_url(shared.database.url.expose(), name)
+        {
+            set_value(&mut merged, "database.url", toml::Value::String(url));
+            set_value(
+                &mut merged,
+                "database.url_file",
+                toml::Value::String(String::new()),
+            );
+        }
+
+        overlay(&mut merged, profile);
+        let mut config: Config = toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e| format!("Invalid profile: {}", e))?;
+        config.profiles.clear();
+        config.read_secret_files().map_err(|e| e.to_string())?;
+        Ok(config)
+    }
+
+    /// Builds and checks every profile. Problems with a value the profile
+    /// sets are reported under `profiles.<name>`, others under the shared
+    /// key.
+    pub fn resolve_profiles(&self) -> Result<Vec<(String, Config)>, ConfigErrors> {
+        let mut problems = Problems::default();
+        let mut resolved: Vec<(String, Config)> = Vec::new();
+
+        for (name, profile) in &self.profiles {
+            let config = match self.profile(name) {
+                Ok(config) => config,
+                Err(e) => {
+                    problems.push(format!("profiles.{}", name), e);
+                    continue;
+                }
+            };
+
+            if let Err(errors) = config.validate() {
+                for problem in errors.problems {
+                    if sets_key(profile, &problem.key) {
+                        problems.push(
+                            format!("profiles.{}.{}", name, problem.key),
+                            problem.message,
+                        );
+                    } else {
+                        problems.push(
+                            problem.key,
+                            format!("In profile '{}': {}", name, problem.message),
+                        );
                     }
+                }
+            }
+
+            if let Some((other, _)) = resolved
+                .iter()
+                .find(|(_, other)| other.database.url == config.database.url)
+            {
+                problems.push(
+                    format!("profiles.{}.database.url", name),
+                    format!(
+                        "Same database as profile '{}'; each profile needs its own database.url",
+                        other
+                    ),
+                );
+            }
 
-                    // Convert back to Config and save the updated version
-                    let updated_config: Config = existing.try_into()?;
-                    let updated_content = toml::to_string_pretty(&updated_config)?;
-                    fs::write(CONFIG_FILE, &updated_content)?;
+            resolved.push((name.clone(), config));
+        }
+
+        problems.finish().map(|()| resolved)
+    }
 
-                    println!("✅ Config file updated with new fields");
-                    Ok(updated_config)
-                } else {
-                    Err(e.into())
+    /// A copy that is safe to print, with every secret replaced.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.discord.webhook_url = Secret::new(REDACTED);
+        config.database.url = Secret::new(REDACTED);
+        for profile in config.profiles.values_mut() {
+            for key in SECRET_KEYS {
+                if sets_key(profile, key) {
+                    set_value(profile, key, toml::Value::String(REDACTED.to_string()));
                 }
             }
         }
+        config
     }
 
-    fn create_default_and_prompt() -> Result<Self, Box<dyn Error>> {
-        println!("🔧 First time setup - Creating configuration file...");
+    /// Writes the default config to `path` for the user to fill in.
+    pub fn write_template(path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string_pretty(&Self::default())?)?;
+        Ok(())
+    }
 
-        let default_config = Self::default();
-        let toml_content = toml::to_string_pretty(&default_config)?;
+    /// Layers `RCT_SECTION__KEY` environment variables over the loaded
+    /// values and returns the keys they set. Variables that can't be
+    /// applied go to `problems`, to be reported with the rest of the
+    /// config's problems. An override beats profiles too, so the keys are
+    /// dropped from every profile. Overrides are never written back to the
+    /// config file.
+    fn with_env_overrides(
+        self,
+        vars: impl IntoIterator<Item = (String, String)>,
+        problems: &mut Problems,
+    ) -> Result<(Self, Vec<String>), Box<dyn Error>> {
+        let mut value = toml::Value::try_from(&self)?;
+        let applied = apply_env_overrides(&mut value, vars, problems);
+        if applied.is_empty() {
+            return Ok((self, applied));
+        }
+
+        info!(
+            "🔧 Config overridden from environment: {}",
+            applied.join(", ")
+        );
+        let mut config: Self = value
+            .try_into()
+            .map_err(|e| format!("❌ Invalid environment override: {}", e))?;
+        for profile in config.profiles.values_mut() {
+            for key in &applied {
+                unset_key(profile, key);
+            }
+        }
+        Ok((config, applied))
+    }
 
-        fs::write(CONFIG_FILE, &toml_content)?;
+    /// Reads the config file, upgrading it in memory if it's from an older
+    /// version or lacks keys added since. The origins locate keys in the
+    /// file as it is on disk: moved keys at their old place and filled-in
+    /// keys as defaults. The upgrade, if any, is returned for the caller to
+    /// write back.
+    fn read_file(path: &Path) -> Result<(Self, ConfigOrigins, Option<Upgrade>), Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let original: toml::Table = toml::from_str(&content)?;
 
-        println!("✅ Created '{}'", CONFIG_FILE);
+        let mut document: DocumentMut = content.parse()?;
+        let defaults = toml::Table::try_from(Self::default())?;
+        let migration =
+            migrate_config(&mut document, &defaults).map_err(|e| format!("❌ {}", e))?;
+        let mut origins = ConfigOrigins::parse(path, content.clone());
+        for (from, to) in &migration.moved {
+            origins.set_moved(from, to);
+        }
+        for key in &migration.added {
+            origins.set_default(key);
+        }
+        if migration.is_empty() {
+            return Ok((toml::from_str(&content)?, origins, None));
+        }
+
+        let content = document.to_string();
+        let config = toml::from_str(&content)?;
+        let version = original
+            .get("config_version")
+            .and_then(toml::Value::as_integer)
+            .unwrap_or(1);
+        Ok((
+            config,
+            origins,
+            Some(Upgrade {
+                version,
+                migration,
+                content,
+            }),
+        ))
+    }
+
+    /// Reads the config file like `read_file` and writes an upgrade back.
+    /// Filling in defaults only adds lines, but before an older version is
+    /// upgraded the original is copied to `<file>.v<version>.bak`. Keys
+    /// filled in keep a default origin.
+    fn load_from_file(path: &Path) -> Result<(Self, ConfigOrigins), Box<dyn Error>> {
+        let (config, origins, upgrade) = Self::read_file(path)?;
+        let Some(upgrade) = upgrade else {
+            return Ok((config, origins));
+        };
+
+        let backup = if upgrade.version < i64::from(CONFIG_VERSION) {
+            let backup = backup_path(path, upgrade.version);
+            fs::copy(path, &backup)?;
+            format!(" (backup at '{}')", backup.display())
+        } else {
+            String::new()
+        };
+        fs::write(path, &upgrade.content)?;
+
+        info!(
+            "🔧 Updated '{}'{}:\n  {}",
+            path.display(),
+            backup,
+            upgrade.migration.changes().join("\n  ")
+        );
+
+        let mut origins = ConfigOrigins::parse(path, upgrade.content);
+        for key in upgrade.migration.added {
+            origins.set_default(key);
+        }
+        Ok((config, origins))
+    }
+
+    fn create_default_and_prompt(path: &Path) -> Result<(Self, ConfigOrigins), Box<dyn Error>> {
+        println!("🔧 First time setup - Creating configuration file...");
+
+        Self::write_template(path)?;
+
+        println!("✅ Created '{}'", path.display());
         println!();
         println!("📝 Please edit the configuration file with your settings:");
         println!("   - Discord webhook URL (REQUIRED)");
@@ -113,68 +857,793 @@ impl Config {
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
 
-        // Reload and validate the config after user edits
-        let config = Self::load_from_file()?;
-        config.validate()?;
-        Ok(config)
+        // Reload the config after user edits
+        Self::load_from_file(path)
     }
 
-    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
-        // Check if webhook URL is still the placeholder
-        if self.discord.webhook_url == "REPLACE_WITH_YOUR_DISCORD_WEBHOOK_URL" 
-            || self.discord.webhook_url.trim().is_empty() {
-            return Err(format!(
-                "❌ Discord webhook URL not configured!\n\
-                Please edit '{}' and set a valid Discord webhook URL.\n\
-                You can get one from your Discord server settings → Integrations → Webhooks",
-                CONFIG_FILE
-            ).into());
+    /// Checks every setting and reports all problems at once.
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut problems = Problems::default();
+
+        // A value read from a file is reported at the key naming the file
+        let webhook_key = if self.discord.webhook_url_file.trim().is_empty() {
+            "discord.webhook_url"
+        } else {
+            "discord.webhook_url_file"
+        };
+        problems.check(
+            webhook_key,
+            check_webhook_url(self.discord.webhook_url.expose()),
+        );
+        problems.check(
+            "discord.bot_avatar_url",
+            check_http_url(&self.discord.bot_avatar_url),
+        );
+
+        for (index, quiet_hours) in self.discord.quiet_hours.iter().enumerate() {
+            problems.check(
+                format!("discord.quiet_hours[{}]", index),
+                quiet_hours.window(),
+            );
+            if let Some(destination) = &quiet_hours.destination
+                && !DESTINATIONS.contains(&destination.as_str())
+            {
+                problems.push(
+                    format!("discord.quiet_hours[{}].destination", index),
+                    format!(
+                        "Unknown destination '{}'. Expected one of: {}",
+                        destination,
+                        DESTINATIONS.join(", ")
+                    ),
+                );
+            }
         }
 
-        // Basic webhook URL validation
-        if !self.discord.webhook_url.starts_with("https://discord.com/api/webhooks/") 
-            && !self.discord.webhook_url.starts_with("https://discordapp.com/api/webhooks/") {
-            return Err(format!(
-                "❌ Invalid Discord webhook URL format!\n\
-                Expected: https://discord.com/api/webhooks/...\n\
-                Got: {}",
-                self.discord.webhook_url
-            ).into());
+        for (index, rule) in self.discord.mentions.iter().enumerate() {
+            for (field, pattern) in rule.patterns() {
+                problems.check(
+                    format!("discord.mentions[{}].{}", index, field),
+                    Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e)),
+                );
+            }
+
+            for (field, ids) in [("roles", &rule.roles), ("users", &rule.users)] {
+                for (id_index, id) in ids.iter().enumerate() {
+                    if !is_snowflake(id) {
+                        problems.push(
+                            format!("discord.mentions[{}].{}[{}]", index, field, id_index),
+                            format!(
+                                "Invalid Discord ID '{}'\n\
+                                Role and user IDs must be numeric (enable Developer Mode and use \"Copy ID\")",
+                                id
+                            ),
+                        );
+                    }
+                }
+            }
         }
 
-        Ok(())
-    }
+        problems.check(
+            "monitoring.commits_url",
+            check_http_url(&self.monitoring.commits_url),
+        );
+        if self.monitoring.check_interval_secs == 0 {
+            problems.push("monitoring.check_interval_secs", "Must be at least 1");
+        }
 
-    pub fn rust_color(&self) -> u32 {
-        // Parse hex color string to u32
-        if self.appearance.embed_color.starts_with('#') {
-            u32::from_str_radix(&self.appearance.embed_color[1..], 16).unwrap_or(0xCD412B)
+        problems.check(
+            "appearance.embed_color",
+            parse_color(&self.appearance.embed_color),
+        );
+        problems.check(
+            "appearance.footer_icon_url",
+            check_http_url(&self.appearance.footer_icon_url),
+        );
+
+        let database_key = if self.database.url_file.trim().is_empty() {
+            "database.url"
         } else {
-            u32::from_str_radix(&self.appearance.embed_color, 16).unwrap_or(0xCD412B)
+            "database.url_file"
+        };
+        problems.check(
+            database_key,
+            Backend::from_url(self.database.url.expose()).map_err(|e| e.to_string()),
+        );
+        if self.database.retention.keep_last < 0 {
+            problems.push(
+                "database.retention.keep_last",
+                "Must not be negative; 0 keeps every commit",
+            );
+        }
+        for (index, limit) in self.database.retention.limits.iter().enumerate() {
+            if limit.keep_last < 0 {
+                problems.push(
+                    format!("database.retention.limits[{}].keep_last", index),
+                    "Must not be negative",
+                );
+            }
+        }
+
+        if self.digest.enabled {
+            match self.digest.timezone() {
+                Ok(timezone) => {
+                    for (name, expression) in [
+                        ("daily_schedule", &self.digest.daily_schedule),
+                        ("weekly_schedule", &self.digest.weekly_schedule),
+                    ] {
+                        if !expression.trim().is_empty() {
+                            problems.check(
+                                format!("digest.{}", name),
+                                Schedule::parse(expression, timezone),
+                            );
+                        }
+                    }
+                }
+                Err(e) => problems.push("digest.timezone", e),
+            }
+        }
+
+        if self.duplicates.enabled && self.duplicates.window_secs == 0 {
+            problems.push("duplicates.window_secs", "Must be at least 1");
+        }
+
+        if self.bursts.enabled && self.bursts.window_secs == 0 {
+            problems.push("bursts.window_secs", "Must be at least 1");
+        }
+
+        if self.backup.enabled {
+            match self.backup.timezone() {
+                Ok(timezone) => problems.check(
+                    "backup.schedule",
+                    Schedule::parse(&self.backup.schedule, timezone),
+                ),
+                Err(e) => problems.push("backup.timezone", e),
+            }
+            if !self.database.url.expose().starts_with("sqlite:") {
+                problems.push(
+                    "backup.enabled",
+                    "Scheduled backups are only supported for SQLite databases",
+                );
+            }
+        }
+
+        if self.lease.enabled && self.lease.ttl_secs <= self.monitoring.check_interval_secs {
+            problems.push(
+                "lease.ttl_secs",
+                format!(
+                    "Must be longer than monitoring.check_interval_secs ({})\n\
+                    The active instance renews its lease once per poll",
+                    self.monitoring.check_interval_secs
+                ),
+            );
+        }
+
+        if self.gaps.enabled {
+            if self.gaps.interval_secs == 0 {
+                problems.push("gaps.interval_secs", "Must be at least 1");
+            }
+            if self.gaps.max_pages == 0 {
+                problems.push("gaps.max_pages", "Must be at least 1");
+            }
         }
+
+        problems.finish()
+    }
+
+    pub fn rust_color(&self) -> u32 {
+        // Only reachable with an unvalidated config
+        parse_color(&self.appearance.embed_color).unwrap_or(0xCD412B)
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            discord: DiscordConfig {
-                webhook_url: "REPLACE_WITH_YOUR_DISCORD_WEBHOOK_URL".to_string(),
-                bot_name: "Rust Commit Tracker".to_string(),
-                bot_avatar_url: "https://i.imgur.com/on47Qk9.png".to_string(),
-            },
-            monitoring: MonitoringConfig {
-                commits_url: "https://commits.facepunch.com/?format=json".to_string(),
-                check_interval_secs: 50,
-            },
-            appearance: AppearanceConfig {
-                embed_color: "#CD412B".to_string(), // Rust orange
-                footer_icon_url: "https://i.imgur.com/on47Qk9.png".to_string(),
-            },
-            database: DatabaseConfig {
-                url: "sqlite:commits.db".to_string(),
-                cleanup_keep_last: 1000,
-            },
+            config_version: CONFIG_VERSION,
+            discord: DiscordConfig::default(),
+            monitoring: MonitoringConfig::default(),
+            appearance: AppearanceConfig::default(),
+            database: DatabaseConfig::default(),
+            digest: DigestConfig::default(),
+            duplicates: DuplicatesConfig::default(),
+            bursts: BurstsConfig::default(),
+            backup: BackupConfig::default(),
+            lease: LeaseConfig::default(),
+            gaps: GapsConfig::default(),
+            authors: AuthorsConfig::default(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: Secret::new("REPLACE_WITH_YOUR_DISCORD_WEBHOOK_URL"),
+            webhook_url_file: String::new(),
+            bot_name: "Rust Commit Tracker".to_string(),
+            bot_avatar_url: "https://i.imgur.com/on47Qk9.png".to_string(),
+            mentions: Vec::new(),
+            quiet_hours: Vec::new(),
+        }
+    }
+}
+
+impl Default for MonitoringConfig {
+    fn default() -> Self {
+        Self {
+            commits_url: "https://commits.facepunch.com/?format=json".to_string(),
+            check_interval_secs: 50,
+        }
+    }
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            embed_color: "#CD412B".to_string(), // Rust orange
+            footer_icon_url: "https://i.imgur.com/on47Qk9.png".to_string(),
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: Secret::new("sqlite:commits.db"),
+            url_file: String::new(),
+            retention: RetentionConfig::default(),
+        }
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            keep_last: 1000,
+            max_age_days: 0,
+            archive_dir: String::new(),
+            limits: Vec::new(),
+        }
+    }
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            daily_schedule: "0 9 * * *".to_string(),
+            weekly_schedule: "0 9 * * 1".to_string(),
+            timezone: "UTC".to_string(),
+            top_authors: 5,
+            top_commits: 5,
+        }
+    }
+}
+
+impl Default for DuplicatesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 3600,
+            edit_original: true,
+        }
+    }
+}
+
+impl Default for BurstsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 180,
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: "0 3 * * *".to_string(),
+            timezone: "UTC".to_string(),
+            directory: "backups".to_string(),
+            keep: 7,
+        }
+    }
+}
+
+impl Default for LeaseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 180,
+            instance_id: String::new(),
+        }
+    }
+}
+
+impl Default for GapsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 900,
+            max_pages: 10,
+            action: GapAction::Deliver,
+        }
+    }
+}
+
+impl Default for AuthorsConfig {
+    fn default() -> Self {
+        Self { announce_new: true }
+    }
+}
+
+/// Checks that a webhook URL is set and looks like a Discord webhook.
+pub fn check_webhook_url(url: &str) -> Result<(), String> {
+    // Check if webhook URL is still the placeholder
+    if url == "REPLACE_WITH_YOUR_DISCORD_WEBHOOK_URL" || url.trim().is_empty() {
+        return Err("Discord webhook URL not configured!\n\
+            Please edit the config file and set a valid Discord webhook URL.\n\
+            You can get one from your Discord server settings → Integrations → Webhooks"
+            .to_string());
+    }
+
+    // Basic webhook URL validation
+    if !url.starts_with("https://discord.com/api/webhooks/")
+        && !url.starts_with("https://discordapp.com/api/webhooks/")
+    {
+        // Only the start of the URL; the rest may be a token
+        let start = match reqwest::Url::parse(url) {
+            Ok(url) => format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default()),
+            Err(_) => "something that isn't a URL".to_string(),
+        };
+        return Err(format!(
+            "Invalid Discord webhook URL format!\n\
+            Expected: https://discord.com/api/webhooks/...\n\
+            Got: {}/...",
+            start
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that a URL is an absolute `http` or `https` URL.
+pub fn check_http_url(url: &str) -> Result<(), String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(parsed) => Err(format!(
+            "Expected an http:// or https:// URL, got '{}:'",
+            parsed.scheme()
+        )),
+        Err(e) => Err(format!("Invalid URL '{}': {}", url, e)),
+    }
+}
+
+/// Parses a `#RRGGBB` or `RRGGBB` hex color.
+pub fn parse_color(color: &str) -> Result<u32, String> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "Invalid color '{}'; expected six hex digits like #CD412B",
+            color
+        ));
+    }
+
+    u32::from_str_radix(hex, 16).map_err(|e| e.to_string())
+}
+
+/// Sets the dotted `key`, creating tables on the way.
+fn set_value(table: &mut toml::Table, key: &str, value: toml::Value) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            let entry = table
+                .entry(head)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if let toml::Value::Table(next) = entry {
+                set_value(next, rest, value);
+            }
+        }
+        None => {
+            table.insert(key.to_string(), value);
         }
     }
 }
+
+fn is_snowflake(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::redact;
+
+    const WEBHOOK: &str = "https://discord.com/api/webhooks/1/token";
+
+    fn valid() -> Config {
+        let mut config = Config::default();
+        config.discord.webhook_url = Secret::new(WEBHOOK);
+        config
+    }
+
+    fn source(path: &Path) -> ConfigSource {
+        ConfigSource {
+            path: path.to_path_buf(),
+            database_url: None,
+            profile: None,
+        }
+    }
+
+    fn problem_keys(config: &Config) -> Vec<String> {
+        match config.validate() {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.problems.into_iter().map(|p| p.key).collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_the_defaults_with_a_webhook() {
+        assert!(valid().validate().is_ok());
+        assert_eq!(problem_keys(&Config::default()), ["discord.webhook_url"]);
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        let mut config = valid();
+        config.discord.bot_avatar_url = "not a url".to_string();
+        config.monitoring.check_interval_secs = 0;
+        config.appearance.embed_color = "#12345".to_string();
+        config.database.url = Secret::new("mysql://localhost/tracker");
+        config.duplicates.enabled = true;
+        config.duplicates.window_secs = 0;
+
+        assert_eq!(
+            problem_keys(&config),
+            [
+                "discord.bot_avatar_url",
+                "monitoring.check_interval_secs",
+                "appearance.embed_color",
+                "database.url",
+                "duplicates.window_secs",
+            ]
+        );
+    }
+
+    #[test]
+    fn names_array_entries_by_index() {
+        let mut config = valid();
+        config.discord.mentions = vec![
+            toml::from_str(r#"message = "fine""#).unwrap(),
+            toml::from_str(
+                r#"branch = "(unclosed"
+               roles = ["123", "admins"]"#,
+            )
+            .unwrap(),
+        ];
+        config.discord.quiet_hours = vec![QuietHours {
+            start: "23:00".to_string(),
+            end: "7am".to_string(),
+            timezone: "UTC".to_string(),
+            destination: Some("slack".to_string()),
+        }];
+        config.database.retention.limits = vec![RetentionLimit {
+            repo: None,
+            branch: None,
+            keep_last: -1,
+        }];
+
+        assert_eq!(
+            problem_keys(&config),
+            [
+                "discord.quiet_hours[0]",
+                "discord.quiet_hours[0].destination",
+                "discord.mentions[1].branch",
+                "discord.mentions[1].roles[1]",
+                "database.retention.limits[0].keep_last",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_values_read_from_files_at_the_file_key() {
+        let mut config = valid();
+        config.discord.webhook_url = Secret::new("https://example.com/hook");
+        config.discord.webhook_url_file = "/run/secrets/webhook".to_string();
+
+        assert_eq!(problem_keys(&config), ["discord.webhook_url_file"]);
+    }
+
+    #[test]
+    fn checks_settings_that_depend_on_each_other() {
+        let mut config = valid();
+        config.lease.enabled = true;
+        config.lease.ttl_secs = config.monitoring.check_interval_secs;
+        config.backup.enabled = true;
+        config.database.url = Secret::new("postgres://localhost/tracker");
+        config.digest.enabled = true;
+        config.digest.timezone = "Mars/Olympus".to_string();
+
+        let keys = problem_keys(&config);
+        assert!(keys.contains(&"lease.ttl_secs".to_string()), "{:?}", keys);
+        assert!(keys.contains(&"backup.enabled".to_string()), "{:?}", keys);
+        assert!(keys.contains(&"digest.timezone".to_string()), "{:?}", keys);
+
+        // Disabled features aren't checked
+        config.lease.enabled = false;
+        config.backup.enabled = false;
+        config.digest.enabled = false;
+        let keys = problem_keys(&config);
+        assert!(!keys.iter().any(|key| key.starts_with("lease.")
+            || key.starts_with("backup.")
+            || key.starts_with("digest.")));
+    }
+
+    #[test]
+    fn registers_only_configured_secrets() {
+        let mut config = Config::default();
+        config.register_secrets();
+        let defaults = format!(
+            "{} {}",
+            config.discord.webhook_url.expose(),
+            config.database.url.expose()
+        );
+        assert_eq!(redact(&defaults), defaults);
+
+        config.discord.webhook_url = Secret::new("https://discord.com/api/webhooks/3/configured");
+        config.register_secrets();
+        assert_eq!(
+            redact("https://discord.com/api/webhooks/3/configured"),
+            REDACTED
+        );
+    }
+
+    #[test]
+    fn accepts_retention_limits_that_keep_nothing() {
+        let mut config = valid();
+        config.database.retention.limits = vec![RetentionLimit {
+            repo: Some("rust_reboot".to_string()),
+            branch: Some("experimental".to_string()),
+            keep_last: 0,
+        }];
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn reads_an_outdated_file_without_writing_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = format!(
+            "[discord]\nwebhook_url = \"{}\"\n\n[database]\ncleanup_keep_last = 500\n",
+            WEBHOOK
+        );
+        fs::write(&path, &content).unwrap();
+        let source = source(&path);
+
+        let config = source.read().unwrap();
+        assert_eq!(config.database.retention.keep_last, 500);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        source.load(false).unwrap();
+        assert_ne!(fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn never_creates_a_missing_file_when_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = source(&dir.path().join("config.toml"));
+
+        assert!(source.read().is_err());
+        assert!(!source.path.exists());
+    }
+
+    #[test]
+    fn writes_a_template_and_stops_when_headless() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = source(&dir.path().join("config.toml"));
+
+        let error = source.load(false).unwrap_err();
+        let created = error.downcast_ref::<ConfigCreated>().unwrap();
+        assert_eq!(created.path, source.path);
+        assert!(
+            created.reason.contains("discord.webhook_url"),
+            "{}",
+            created
+        );
+        assert!(source.path.exists());
+
+        // The template is only written once
+        fs::write(&source.path, "# Edited\n").unwrap();
+        let error = source.load(false).unwrap_err();
+        assert!(!error.is::<ConfigCreated>());
+        let edited = fs::read_to_string(&source.path).unwrap();
+        assert!(edited.contains("# Edited"), "{}", edited);
+    }
+
+    #[test]
+    fn keeps_every_backup_of_an_upgraded_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let outdated = "# Old settings\n[database]\ncleanup_keep_last = 500\n";
+
+        for _ in 0..2 {
+            fs::write(&path, outdated).unwrap();
+            Config::load_from_file(&path).unwrap();
+        }
+        let upgraded = fs::read_to_string(&path).unwrap();
+        assert!(upgraded.contains("# Old settings"), "{}", upgraded);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("config.toml.v1.bak")).unwrap(),
+            outdated
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("config.toml.v1.1.bak")).unwrap(),
+            outdated
+        );
+
+        // Filling in a key that's missing needs no backup
+        fs::write(&path, upgraded.replace("archive_dir", "# archive_dir")).unwrap();
+        Config::load_from_file(&path).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn reports_filled_in_keys_as_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = format!(
+            "config_version = {}\n\n[discord]\nwebhook_url = \"{}\"\n",
+            CONFIG_VERSION, WEBHOOK
+        );
+        fs::write(&path, &content).unwrap();
+        let file = path.display().to_string();
+
+        for origins in [
+            source(&path).read_with_origins().unwrap().1,
+            Config::load_from_file(&path).unwrap().1,
+        ] {
+            assert_eq!(
+                origins.origin("discord.webhook_url"),
+                Some(format!("{}:4:15", file))
+            );
+            assert_eq!(origins.origin("discord.bot_name"), None);
+            assert_eq!(origins.origin("database.retention.keep_last"), None);
+        }
+        assert!(fs::read_to_string(&path).unwrap().contains("bot_name"));
+    }
+
+    #[test]
+    fn applies_env_overrides_over_profiles() {
+        let mut config = valid();
+        config.profiles.insert(
+            "staging".to_string(),
+            toml::from_str(
+                r#"
+                [monitoring]
+                check_interval_secs = 30
+                commits_url = "https://example.com/staging"
+                "#,
+            )
+            .unwrap(),
+        );
+        let vars = [(
+            "RCT_MONITORING__CHECK_INTERVAL_SECS".to_string(),
+            "5".to_string(),
+        )];
+
+        let mut problems = Problems::default();
+        let (config, applied) = config.with_env_overrides(vars, &mut problems).unwrap();
+        problems.finish().unwrap();
+        assert_eq!(applied, ["monitoring.check_interval_secs"]);
+        let staging = config.profile("staging").unwrap();
+        assert_eq!(staging.monitoring.check_interval_secs, 5);
+        assert_eq!(
+            staging.monitoring.commits_url,
+            "https://example.com/staging"
+        );
+    }
+
+    #[test]
+    fn reports_malformed_overrides_with_other_problems() {
+        let mut config = valid();
+        config.discord.bot_avatar_url = "not a url".to_string();
+        let vars = [
+            (
+                "RCT_MONITORING__CHECK_INTERVAL_SECS".to_string(),
+                "soon".to_string(),
+            ),
+            ("RCT_LEASE__TTL".to_string(), "60".to_string()),
+        ];
+
+        let mut problems = Problems::default();
+        let (config, applied) = config.with_env_overrides(vars, &mut problems).unwrap();
+        assert!(applied.is_empty());
+        let errors = problems
+            .finish_with(config.validate())
+            .unwrap_err()
+            .locate(&ConfigOrigins::new(Path::new("config.toml")));
+
+        let message = errors.to_string();
+        assert!(message.starts_with("Found 3 problems"), "{}", message);
+        assert!(
+            message.contains(
+                "monitoring.check_interval_secs (RCT_MONITORING__CHECK_INTERVAL_SECS): \
+                 Expected an integer, got 'soon'"
+            ),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("lease.ttl (RCT_LEASE__TTL): Unknown config key"),
+            "{}",
+            message
+        );
+        assert!(message.contains("discord.bot_avatar_url"), "{}", message);
+    }
+
+    #[test]
+    fn locates_moved_keys_where_the_file_sets_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = format!(
+            "[discord]\nwebhook_url = \"{}\"\n\n[database]\ncleanup_keep_last = 300\n",
+            WEBHOOK
+        );
+        fs::write(&path, &content).unwrap();
+        let file = path.display().to_string();
+
+        let (config, origins) = source(&path).read_with_origins().unwrap();
+        assert_eq!(config.database.retention.keep_last, 300);
+        assert_eq!(
+            origins.origin("database.retention.keep_last"),
+            Some(format!("{}:5:21", file))
+        );
+        assert_eq!(origins.origin("database.retention.max_age_days"), None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        // Once written back, the key is found at its new place
+        let (_, origins) = Config::load_from_file(&path).unwrap();
+        let origin = origins.origin("database.retention.keep_last").unwrap();
+        let upgraded = fs::read_to_string(&path).unwrap();
+        let line: usize = origin.rsplit(':').nth(1).unwrap().parse().unwrap();
+        assert!(
+            upgraded
+                .lines()
+                .nth(line - 1)
+                .unwrap()
+                .starts_with("keep_last = 300"),
+            "{} in:\n{}",
+            origin,
+            upgraded
+        );
+    }
+
+    /// Properties under `schema` without a description, as dotted paths.
+    fn undescribed(schema: &serde_json::Value, prefix: &str, missing: &mut Vec<String>) {
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (key, property) in properties {
+                let path = format!("{}{}", prefix, key);
+                if property.get("description").is_none() {
+                    missing.push(path.clone());
+                }
+                undescribed(property, &format!("{}.", path), missing);
+            }
+        }
+        if let Some(definitions) = schema.get("$defs").and_then(|d| d.as_object()) {
+            for (name, definition) in definitions {
+                undescribed(definition, &format!("{}.", name), missing);
+            }
+        }
+    }
+
+    #[test]
+    fn describes_every_key_in_the_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Config)).unwrap();
+        let mut missing = Vec::new();
+        undescribed(&schema, "", &mut missing);
+        assert!(missing.is_empty(), "no description for {:?}", missing);
+    }
+}
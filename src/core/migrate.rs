@@ -0,0 +1,436 @@
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// Version of the config file format written by this build. Files without a
+/// `config_version` key are version 1.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Upgrades a config file from `to - 1` to `to`, e.g. by renaming keys.
+/// Missing keys don't need a step; they're filled in from the defaults.
+struct Step {
+    to: u32,
+    description: &'static str,
+    apply: fn(&mut Table, &mut Migration),
+}
+
+/// Every upgrade, oldest first. Add a step and bump `CONFIG_VERSION` when
+/// a key is renamed or moved.
+const STEPS: &[Step] = &[Step {
+    to: 2,
+    description: "database.cleanup_keep_last moved to database.retention.keep_last",
+    apply: move_cleanup_keep_last,
+}];
+
+fn move_cleanup_keep_last(config: &mut Table, migration: &mut Migration) {
+    migration.rename(
+        config,
+        "database.cleanup_keep_last",
+        "database.retention.keep_last",
+    );
+}
+
+/// What `migrate_config` changed.
+#[derive(Debug, Default)]
+pub struct Migration {
+    /// The upgrade steps run, e.g. `v2: a moved to b`
+    pub steps: Vec<String>,
+    /// Dotted keys filled in from the defaults
+    pub added: Vec<String>,
+    /// Keys whose value a step moved, as `(from, to)`
+    pub moved: Vec<(String, String)>,
+}
+
+impl Migration {
+    /// Renames `from` to `to`, noting the move if the value went along.
+    fn rename(&mut self, config: &mut Table, from: &str, to: &str) {
+        if rename(config, from, to) {
+            self.moved.push((from.to_string(), to.to_string()));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty() && self.added.is_empty()
+    }
+
+    /// A description of each change, steps first.
+    pub fn changes(&self) -> Vec<String> {
+        let added = self.added.iter().map(|key| format!("added {}", key));
+        self.steps.iter().cloned().chain(added).collect()
+    }
+}
+
+/// Brings a config file up to `CONFIG_VERSION`: runs the steps newer than
+/// its `config_version`, then copies in every key from `defaults` that is
+/// missing at any depth. The document is edited in place, so comments and
+/// layout survive.
+pub fn migrate_config(
+    config: &mut DocumentMut,
+    defaults: &toml::Table,
+) -> Result<Migration, String> {
+    let version = match config.get("config_version") {
+        None => 1,
+        Some(item) => match item.as_integer() {
+            Some(version) if version >= 1 => version as u32,
+            _ => {
+                return Err(format!(
+                    "Invalid config_version {}",
+                    item.to_string().trim()
+                ));
+            }
+        },
+    };
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "The config file is version {}, but this build only understands up to version {}. \
+            Upgrade rust-commit-tracker",
+            version, CONFIG_VERSION
+        ));
+    }
+
+    let mut migration = Migration::default();
+    if version < CONFIG_VERSION {
+        for step in STEPS.iter().filter(|step| step.to > version) {
+            (step.apply)(config.as_table_mut(), &mut migration);
+            migration
+                .steps
+                .push(format!("v{}: {}", step.to, step.description));
+        }
+        set_version(config.as_table_mut());
+    }
+
+    merge_defaults("", config.as_table_mut(), defaults, &mut migration.added);
+    Ok(migration)
+}
+
+/// Sets `config_version` to `CONFIG_VERSION`, keeping any comment on it.
+fn set_version(config: &mut Table) {
+    let version = Value::from(i64::from(CONFIG_VERSION));
+    match config
+        .get_mut("config_version")
+        .and_then(Item::as_value_mut)
+    {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = version;
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            config.insert("config_version", Item::Value(version));
+        }
+    }
+}
+
+/// Adds keys from `defaults` that `config` lacks, recursing into tables
+/// both have. Arrays are left alone since their entries have no defaults.
+fn merge_defaults(
+    prefix: &str,
+    config: &mut dyn TableLike,
+    defaults: &toml::Table,
+    added: &mut Vec<String>,
+) {
+    for (key, default) in defaults {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match config.get_mut(key) {
+            None => {
+                config.insert(key, item(default));
+                added.push(path);
+            }
+            Some(existing) => {
+                if let (Some(table), toml::Value::Table(default)) =
+                    (existing.as_table_like_mut(), default)
+                {
+                    merge_defaults(&path, table, default, added)
+                }
+            }
+        }
+    }
+}
+
+/// A default value as a document item. Tables become `[sections]` placed
+/// after their parent; everything else is written inline.
+fn item(value: &toml::Value) -> Item {
+    match value {
+        toml::Value::Table(values) => {
+            let mut table = Table::new();
+            for (key, value) in values {
+                table.insert(key, item(value));
+            }
+            Item::Table(table)
+        }
+        value => Item::Value(
+            value
+                .to_string()
+                .parse()
+                .expect("a serialized TOML value parses"),
+        ),
+    }
+}
+
+/// Moves the value at dotted key `from` to `to`, creating tables on the
+/// way. A value already at `to` wins and the old key is dropped. Nothing
+/// moves if something other than a table is in the way. Returns whether the
+/// value ended up at `to`.
+fn rename(config: &mut Table, from: &str, to: &str) -> bool {
+    let (parents, last) = match to.rsplit_once('.') {
+        Some((parents, last)) => (Some(parents), last),
+        None => (None, to),
+    };
+    if parents.is_some_and(|parents| !reachable(config, parents)) {
+        return false;
+    }
+    let Some(value) = take(config, from) else {
+        return false;
+    };
+
+    let mut table: &mut dyn TableLike = config;
+    for segment in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        let mut new = Table::new();
+        new.set_implicit(true);
+        let Some(next) = table
+            .entry(segment)
+            .or_insert(Item::Table(new))
+            .as_table_like_mut()
+        else {
+            return false;
+        };
+        table = next;
+    }
+    if table.get(last).is_some() {
+        return false;
+    }
+    table.insert(last, value);
+    true
+}
+
+/// Whether every table along dotted `path` exists or can be created.
+fn reachable(table: &dyn TableLike, path: &str) -> bool {
+    let (head, rest) = match path.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (path, None),
+    };
+    match table.get(head) {
+        None => true,
+        Some(item) => match (item.as_table_like(), rest) {
+            (Some(next), Some(rest)) => reachable(next, rest),
+            (Some(_), None) => true,
+            (None, _) => false,
+        },
+    }
+}
+
+fn take(table: &mut dyn TableLike, key: &str) -> Option<Item> {
+    match key.split_once('.') {
+        Some((head, rest)) => take(table.get_mut(head)?.as_table_like_mut()?, rest),
+        None => table.remove(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> toml::Table {
+        toml::from_str(
+            r#"
+            config_version = 2
+
+            [monitoring]
+            check_interval_secs = 50
+            commits_url = "https://example.com"
+
+            [database]
+            url = "sqlite:commits.db"
+
+            [database.retention]
+            keep_last = 0
+            limits = []
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn document(content: &str) -> DocumentMut {
+        content.parse().unwrap()
+    }
+
+    /// The migrated file, parsed back, and the changes made.
+    fn migrate(content: &str) -> Result<(toml::Table, Vec<String>), String> {
+        let mut config = document(content);
+        let migration = migrate_config(&mut config, &defaults())?;
+        Ok((
+            toml::from_str(&config.to_string()).unwrap(),
+            migration.changes(),
+        ))
+    }
+
+    #[test]
+    fn moves_renamed_keys_from_version_one() {
+        let (config, changes) = migrate(
+            r#"
+            [database]
+            url = "sqlite:tracker.db"
+            cleanup_keep_last = 500
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config["config_version"].as_integer(), Some(2));
+        let database = config["database"].as_table().unwrap();
+        assert!(!database.contains_key("cleanup_keep_last"));
+        assert_eq!(database["url"].as_str(), Some("sqlite:tracker.db"));
+        assert_eq!(database["retention"]["keep_last"].as_integer(), Some(500));
+        assert_eq!(
+            changes[0],
+            "v2: database.cleanup_keep_last moved to database.retention.keep_last"
+        );
+    }
+
+    #[test]
+    fn keeps_a_value_already_at_the_new_key() {
+        let (config, _) = migrate(
+            r#"
+            [database]
+            cleanup_keep_last = 500
+
+            [database.retention]
+            keep_last = 100
+            "#,
+        )
+        .unwrap();
+
+        let database = config["database"].as_table().unwrap();
+        assert!(!database.contains_key("cleanup_keep_last"));
+        assert_eq!(database["retention"]["keep_last"].as_integer(), Some(100));
+    }
+
+    #[test]
+    fn fills_in_missing_keys_at_any_depth() {
+        let (config, changes) = migrate(
+            r#"
+            config_version = 2
+
+            [monitoring]
+            check_interval_secs = 10
+
+            [database.retention]
+            limits = [{ keep_last = 5 }]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            changes,
+            [
+                "added database.retention.keep_last",
+                "added database.url",
+                "added monitoring.commits_url",
+            ]
+        );
+        assert_eq!(
+            config["monitoring"]["check_interval_secs"].as_integer(),
+            Some(10)
+        );
+        // Arrays keep their entries as they are
+        let limits = config["database"]["retention"]["limits"]
+            .as_array()
+            .unwrap();
+        assert_eq!(limits.len(), 1);
+        assert!(limits[0].get("repo").is_none());
+    }
+
+    #[test]
+    fn fills_in_inline_tables() {
+        let (config, changes) = migrate(
+            r#"
+            config_version = 2
+            monitoring = { check_interval_secs = 10, commits_url = "https://example.org" }
+            database = { url = "sqlite:tracker.db", retention = { limits = [] } }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(changes, ["added database.retention.keep_last"]);
+        assert_eq!(
+            config["database"]["retention"]["keep_last"].as_integer(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_layout() {
+        let mut config = document(
+            r#"# Tracker settings
+[monitoring]
+# Poll often
+check_interval_secs = 10  # seconds
+
+[database]
+cleanup_keep_last = 500 # plenty
+"#,
+        );
+        migrate_config(&mut config, &defaults()).unwrap();
+
+        let migrated = config.to_string();
+        for kept in [
+            "# Tracker settings\n[monitoring]\n# Poll often\ncheck_interval_secs = 10  # seconds\n",
+            "keep_last = 500 # plenty",
+        ] {
+            assert!(migrated.contains(kept), "{:?} not in:\n{}", kept, migrated);
+        }
+    }
+
+    #[test]
+    fn leaves_a_current_file_alone() {
+        let content = r#"
+            config_version = 2
+
+            [monitoring]
+            check_interval_secs = 10
+            commits_url = "https://example.org"
+
+            [database]
+            url = "sqlite:tracker.db"
+
+            [database.retention]
+            keep_last = 0
+            limits = []
+            "#;
+        let mut config = document(content);
+        let migration = migrate_config(&mut config, &defaults()).unwrap();
+        assert!(migration.is_empty(), "{:?}", migration);
+        assert_eq!(config.to_string(), content);
+    }
+
+    #[test]
+    fn refuses_newer_and_invalid_versions() {
+        let error = migrate("config_version = 3").unwrap_err();
+        assert!(
+            error.contains("only understands up to version 2"),
+            "{}",
+            error
+        );
+
+        for version in ["0", "-1", "\"2\""] {
+            let error = migrate(&format!("config_version = {}", version)).unwrap_err();
+            assert!(error.starts_with("Invalid config_version"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn renames_through_missing_tables() {
+        let mut config = document("old = 1\n[a]\nb = 2\n");
+        rename(config.as_table_mut(), "old", "x.y.z");
+        assert_eq!(config["x"]["y"]["z"].as_integer(), Some(1));
+        assert!(config.get("old").is_none());
+
+        // Missing keys and paths through non-tables change nothing
+        let before = config.to_string();
+        rename(config.as_table_mut(), "missing", "a.c");
+        rename(config.as_table_mut(), "x.y.z", "a.b.c");
+        assert_eq!(config.to_string(), before);
+    }
+}
@@ -1,5 +1,21 @@
 pub mod config;
+pub mod migrate;
+pub mod origins;
+pub mod overrides;
+pub mod profiles;
+pub mod reload;
+pub mod schedule;
+pub mod secret;
 pub mod tracker;
+pub mod validation;
 
 pub use config::*;
+pub use migrate::*;
+pub use origins::*;
+pub use overrides::*;
+pub use profiles::*;
+pub use reload::*;
+pub use schedule::*;
+pub use secret::*;
 pub use tracker::*;
+pub use validation::*;
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item};
+
+/// Where each config value was set: the config file, an environment
+/// variable, a command-line flag or a secret file. Keys set nowhere hold
+/// their default.
+#[derive(Debug, Clone)]
+pub struct ConfigOrigins {
+    path: PathBuf,
+    document: Option<ImDocument<String>>,
+    /// Keys set outside the config file, with their source
+    overrides: BTreeMap<String, String>,
+    /// Keys the file holds only because an upgrade filled in their default
+    defaulted: Vec<String>,
+    /// Keys an upgrade moved, as `(from, to)`, found at `from` in the file
+    moved: Vec<(String, String)>,
+    /// Profile whose `profiles.<name>` keys take precedence
+    profile: Option<String>,
+}
+
+impl ConfigOrigins {
+    /// Reads the config file at `path` to find the keys it sets.
+    pub fn new(path: &Path) -> Self {
+        Self::parse(path, fs::read_to_string(path).unwrap_or_default())
+    }
+
+    /// Finds the keys set in `content`, the config file at `path`.
+    pub fn parse(path: &Path, content: String) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            document: ImDocument::parse(content).ok(),
+            overrides: BTreeMap::new(),
+            defaulted: Vec::new(),
+            moved: Vec::new(),
+            profile: None,
+        }
+    }
+
+    /// Looks keys up in profile `name` first, then in the shared settings.
+    pub fn for_profile(mut self, name: &str) -> Self {
+        self.profile = Some(name.to_string());
+        self
+    }
+
+    /// Records that `key` was set by `source` rather than the file.
+    pub fn set(&mut self, key: impl Into<String>, source: impl Into<String>) {
+        self.overrides.insert(key.into(), source.into());
+    }
+
+    /// Records that the file holds `key`'s default only because an upgrade
+    /// wrote it there.
+    pub fn set_default(&mut self, key: impl Into<String>) {
+        self.defaulted.push(key.into());
+    }
+
+    /// Records that an upgrade moved the value at `from` in the file to
+    /// `to`, which is then located at `from`.
+    pub fn set_moved(&mut self, from: impl Into<String>, to: impl Into<String>) {
+        self.moved.push((from.into(), to.into()));
+    }
+
+    /// Where `key` was set, as `file:line:column`, an environment variable
+    /// or a flag. `None` for a default. Keys inside a table or array that
+    /// was overridden as a whole share its origin. Overrides of a shared key
+    /// apply to every profile, so they beat the profile's own settings.
+    pub fn origin(&self, key: &str) -> Option<String> {
+        if let Some(profile) = &self.profile {
+            let profile_key = format!("profiles.{}.{}", profile, key);
+            if let Some(origin) = self
+                .overridden(&profile_key)
+                .or_else(|| self.overridden(key))
+                .or_else(|| self.in_file(&profile_key))
+            {
+                return Some(origin);
+            }
+        }
+        self.overridden(key).or_else(|| self.in_file(key))
+    }
+
+    fn overridden(&self, key: &str) -> Option<String> {
+        self.overrides
+            .iter()
+            .find(|(overridden, _)| within(key, overridden))
+            .map(|(_, source)| source.clone())
+    }
+
+    fn in_file(&self, key: &str) -> Option<String> {
+        if self
+            .defaulted
+            .iter()
+            .any(|defaulted| within(key, defaulted))
+        {
+            return None;
+        }
+
+        let moved = self.moved.iter().find_map(|(from, to)| {
+            key.strip_prefix(to.as_str())
+                .filter(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+                .map(|rest| format!("{}{}", from, rest))
+        });
+        let key = moved.as_deref().unwrap_or(key);
+
+        let document = self.document.as_ref()?;
+        let offset = find(document.as_item(), key)?;
+        let (line, column) = line_column(document.raw(), offset);
+        Some(format!("{}:{}:{}", self.path.display(), line, column))
+    }
+}
+
+/// Whether `key` is `parent` or lies inside it.
+fn within(key: &str, parent: &str) -> bool {
+    key.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+}
+
+/// Byte offset of the value at `key` in a parsed document. `key` is dotted
+/// with array indices, e.g. `discord.mentions[0].roles`.
+fn find(root: &Item, key: &str) -> Option<usize> {
+    let mut item = root;
+    for segment in key.split('.') {
+        let mut parts = segment.split('[');
+        item = item.get(parts.next()?)?;
+        for index in parts {
+            item = item.get(index.trim_end_matches(']').parse::<usize>().ok()?)?;
+        }
+    }
+
+    item.span().map(|span| span.start)
+}
+
+/// One-based line and column of a byte offset.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
@@ -0,0 +1,301 @@
+use crate::core::Problems;
+use toml::Value;
+
+/// Prefix of environment variables that override config values. Sections
+/// and keys are separated by a double underscore, so
+/// `RCT_MONITORING__CHECK_INTERVAL_SECS` sets `monitoring.check_interval_secs`.
+pub const ENV_PREFIX: &str = "RCT_";
+
+/// Name of the environment variable that overrides a dotted config key.
+pub fn env_var_name(key: &str) -> String {
+    format!(
+        "{}{}",
+        ENV_PREFIX,
+        key.replace('.', "__").to_ascii_uppercase()
+    )
+}
+
+/// Layers `RCT_*` variables over a parsed config. Each value is parsed as
+/// the type the key already has; arrays and tables are written as inline
+/// TOML. Returns the dotted keys that were overridden. Variables naming an
+/// unknown key or holding a malformed value are added to `problems`
+/// instead, at the key they name.
+pub(crate) fn apply_env_overrides(
+    config: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+    problems: &mut Problems,
+) -> Vec<String> {
+    let mut applied = Vec::new();
+
+    let mut vars: Vec<_> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    vars.sort();
+
+    for (name, raw) in vars {
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split("__")
+            .map(|segment| segment.to_ascii_lowercase())
+            .collect();
+        let key = path.join(".");
+
+        let Some(slot) = lookup(config, &path) else {
+            problems.push_from(key, name, "Unknown config key");
+            continue;
+        };
+
+        match parse_like(slot, &raw) {
+            Ok(value) => {
+                *slot = value;
+                applied.push(key);
+            }
+            Err(expected) => {
+                problems.push_from(key, name, format!("Expected {}, got '{}'", expected, raw))
+            }
+        }
+    }
+
+    applied
+}
+
+fn lookup<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    if path.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+
+    path.iter().try_fold(value, |value, segment| {
+        value.as_table_mut()?.get_mut(segment)
+    })
+}
+
+/// Parses `raw` as the same kind of value as `current`. On failure returns
+/// a description of what was expected.
+fn parse_like(current: &Value, raw: &str) -> Result<Value, &'static str> {
+    match current {
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+        Value::Integer(_) => raw
+            .trim()
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| "an integer"),
+        Value::Float(_) => raw.trim().parse().map(Value::Float).map_err(|_| "a number"),
+        Value::Boolean(_) => match raw.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(Value::Boolean(true)),
+            "false" | "0" | "no" | "off" => Ok(Value::Boolean(false)),
+            _ => Err("true or false"),
+        },
+        Value::Array(_) => match parse_inline(raw) {
+            Some(value @ Value::Array(_)) => Ok(value),
+            _ => Err("an inline TOML array like [\"a\", \"b\"]"),
+        },
+        Value::Table(_) => match parse_inline(raw) {
+            Some(value @ Value::Table(_)) => Ok(value),
+            _ => Err("an inline TOML table like { key = \"value\" }"),
+        },
+        Value::Datetime(_) => raw
+            .trim()
+            .parse()
+            .map(Value::Datetime)
+            .map_err(|_| "a TOML date-time"),
+    }
+}
+
+fn parse_inline(raw: &str) -> Option<Value> {
+    let mut document: toml::Table = toml::from_str(&format!("value = {}", raw)).ok()?;
+    document.remove("value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Config, ConfigErrors};
+
+    fn config() -> Value {
+        Value::try_from(Config::default()).unwrap()
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Applies `pairs`, failing on any problem.
+    fn apply(config: &mut Value, pairs: &[(&str, &str)]) -> Vec<String> {
+        let mut problems = Problems::default();
+        let applied = apply_env_overrides(config, vars(pairs), &mut problems);
+        problems.finish().unwrap();
+        applied
+    }
+
+    /// Applies `pairs` and returns the problems found.
+    fn problems(pairs: &[(&str, &str)]) -> ConfigErrors {
+        let mut problems = Problems::default();
+        apply_env_overrides(&mut config(), vars(pairs), &mut problems);
+        problems.finish().unwrap_err()
+    }
+
+    #[test]
+    fn names_variables_after_dotted_keys() {
+        assert_eq!(
+            env_var_name("monitoring.check_interval_secs"),
+            "RCT_MONITORING__CHECK_INTERVAL_SECS"
+        );
+    }
+
+    #[test]
+    fn parses_values_as_the_type_they_replace() {
+        let mut config = config();
+        let applied = apply(
+            &mut config,
+            &[
+                ("RCT_MONITORING__CHECK_INTERVAL_SECS", " 30 "),
+                ("RCT_MONITORING__COMMITS_URL", "https://example.org"),
+                ("RCT_LEASE__ENABLED", "on"),
+                ("PATH", "/usr/bin"),
+            ],
+        );
+
+        assert_eq!(
+            applied,
+            [
+                "lease.enabled",
+                "monitoring.check_interval_secs",
+                "monitoring.commits_url",
+            ]
+        );
+        assert_eq!(
+            config["monitoring"]["check_interval_secs"].as_integer(),
+            Some(30)
+        );
+        assert_eq!(
+            config["monitoring"]["commits_url"].as_str(),
+            Some("https://example.org")
+        );
+        assert_eq!(config["lease"]["enabled"].as_bool(), Some(true));
+        assert_eq!(
+            parse_like(&Value::Float(0.5), "0.25"),
+            Ok(Value::Float(0.25))
+        );
+    }
+
+    #[test]
+    fn accepts_the_usual_spellings_of_booleans() {
+        for (raw, expected) in [
+            ("true", true),
+            ("YES", true),
+            ("1", true),
+            ("On", true),
+            ("false", false),
+            ("no", false),
+            ("0", false),
+            ("OFF", false),
+        ] {
+            assert_eq!(
+                parse_like(&Value::Boolean(!expected), raw),
+                Ok(Value::Boolean(expected)),
+                "{}",
+                raw
+            );
+        }
+        assert!(parse_like(&Value::Boolean(true), "maybe").is_err());
+    }
+
+    #[test]
+    fn parses_inline_arrays_and_tables() {
+        let mut config = config();
+        apply(
+            &mut config,
+            &[
+                (
+                    "RCT_DISCORD__QUIET_HOURS",
+                    r#"[{ start = "23:00", end = "07:00", timezone = "UTC" }]"#,
+                ),
+                (
+                    "RCT_DISCORD__MENTIONS",
+                    r#"[{ branch = "^main$", roles = ["10"] }]"#,
+                ),
+            ],
+        );
+
+        let config: Config = config.try_into().unwrap();
+        assert_eq!(config.discord.quiet_hours[0].start, "23:00");
+        assert_eq!(config.discord.mentions[0].branch.as_deref(), Some("^main$"));
+        assert_eq!(config.discord.mentions[0].roles, ["10"]);
+
+        // An array where a table is expected and vice versa
+        let table = Value::Table(toml::Table::new());
+        assert!(parse_like(&table, "{ a = 1 }").is_ok());
+        assert!(parse_like(&Value::Array(Vec::new()), "{ a = 1 }").is_err());
+        assert!(parse_like(&table, "[1, 2]").is_err());
+        assert!(parse_like(&Value::Array(Vec::new()), "[1, ").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for name in [
+            "RCT_MONITORING__NOPE",
+            "RCT_NOPE",
+            "RCT_MONITORING__CHECK_INTERVAL_SECS__DEEPER",
+            "RCT_MONITORING____CHECK_INTERVAL_SECS",
+            "RCT_",
+        ] {
+            let errors = problems(&[(name, "1")]);
+            assert_eq!(errors.problems.len(), 1, "{}", errors);
+            assert_eq!(errors.problems[0].message, "Unknown config key");
+            assert_eq!(errors.problems[0].origin.as_deref(), Some(name));
+        }
+    }
+
+    #[test]
+    fn reports_every_malformed_value() {
+        let mut config = config();
+        let mut problems = Problems::default();
+        let applied = apply_env_overrides(
+            &mut config,
+            vars(&[
+                ("RCT_MONITORING__CHECK_INTERVAL_SECS", "soon"),
+                ("RCT_LEASE__ENABLED", "maybe"),
+                ("RCT_MONITORING__NOPE", "1"),
+                ("RCT_MONITORING__COMMITS_URL", "https://example.org"),
+            ]),
+            &mut problems,
+        );
+        assert_eq!(applied, ["monitoring.commits_url"]);
+
+        let errors = problems.finish().unwrap_err();
+        let found: Vec<(&str, &str, &str)> = errors
+            .problems
+            .iter()
+            .map(|p| {
+                (
+                    p.key.as_str(),
+                    p.origin.as_deref().unwrap(),
+                    p.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "lease.enabled",
+                    "RCT_LEASE__ENABLED",
+                    "Expected true or false, got 'maybe'"
+                ),
+                (
+                    "monitoring.check_interval_secs",
+                    "RCT_MONITORING__CHECK_INTERVAL_SECS",
+                    "Expected an integer, got 'soon'"
+                ),
+                (
+                    "monitoring.nope",
+                    "RCT_MONITORING__NOPE",
+                    "Unknown config key"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::future::Future;
+use toml::{Table, Value};
+
+tokio::task_local! {
+    static PROFILE: String;
+}
+
+/// Runs `future` as the tracker for profile `name`, tagging its log lines.
+pub async fn in_profile<F: Future>(name: String, future: F) -> F::Output {
+    PROFILE.scope(name, future).await
+}
+
+/// Name of the profile the current task runs, if any.
+pub fn current_profile() -> Option<String> {
+    PROFILE.try_with(Clone::clone).ok()
+}
+
+/// Profile names end up in file names and log lines.
+pub fn check_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid profile name '{}'; use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Layers a profile's settings over the shared ones. Tables are merged key
+/// by key; anything else, arrays included, is replaced.
+pub fn overlay(base: &mut Table, profile: &Table) {
+    for (key, value) in profile {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(profile)) => overlay(base, profile),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Whether `table` sets the dotted `key` or a table or array containing
+/// it. Array indices in `key` are ignored.
+pub fn sets_key(table: &Table, key: &str) -> bool {
+    let mut table = table;
+    for segment in key.split('.') {
+        let name = segment.split('[').next().unwrap_or(segment);
+        match table.get(name) {
+            Some(Value::Table(next)) => table = next,
+            Some(_) => return true,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Removes the dotted `key` from `table`, letting the shared value through.
+pub fn unset_key(table: &mut Table, key: &str) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            if let Some(Value::Table(next)) = table.get_mut(head) {
+                unset_key(next, rest);
+            }
+        }
+        None => {
+            table.remove(key);
+        }
+    }
+}
+
+/// Gives each profile its own SQLite file next to the shared one, e.g.
+/// `sqlite:commits.db` becomes `sqlite:commits-community.db`. Other
+/// databases can't be derived and must be set per profile.
+pub fn profile_database_url(url: &str, profile: &str) -> Option<String> {
+    let rest = url.strip_prefix("sqlite:")?;
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, format!("?{}", query)),
+        None => (rest, String::new()),
+    };
+
+    let name_start = path.rfind('/').map_or(0, |slash| slash + 1);
+    let path = match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = name_start + dot;
+            format!("{}-{}{}", &path[..dot], profile, &path[dot..])
+        }
+        _ => format!("{}-{}", path, profile),
+    };
+    Some(format!("sqlite:{}{}", path, query))
+}
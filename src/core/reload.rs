@@ -0,0 +1,158 @@
+use crate::core::{Config, ConfigSource, SECRET_KEYS};
+use log::{debug, info};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tokio::time::{Instant, sleep_until};
+
+/// How often the config file's modification time is checked.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Notices when the config should be reloaded: the file was saved, or the
+/// process received SIGHUP.
+pub struct ConfigWatcher {
+    source: ConfigSource,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    hangup: Option<tokio::signal::unix::Signal>,
+}
+
+impl ConfigWatcher {
+    pub fn new(source: ConfigSource) -> Self {
+        let modified = modified_at(&source);
+
+        #[cfg(unix)]
+        let hangup = {
+            use tokio::signal::unix::{SignalKind, signal};
+            match signal(SignalKind::hangup()) {
+                Ok(hangup) => Some(hangup),
+                Err(e) => {
+                    log::warn!("⚠️  Can't listen for SIGHUP: {}", e);
+                    None
+                }
+            }
+        };
+
+        Self {
+            source,
+            modified,
+            #[cfg(unix)]
+            hangup,
+        }
+    }
+
+    /// Waits up to `timeout`. Returns true as soon as a reload is requested.
+    pub async fn wait(&mut self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let next_check = deadline.min(Instant::now() + WATCH_INTERVAL);
+            tokio::select! {
+                _ = sleep_until(next_check) => {}
+                _ = self.hangup() => {
+                    info!("🔄 Received SIGHUP, reloading config");
+                    return true;
+                }
+            }
+
+            let modified = modified_at(&self.source);
+            if modified.is_some() && modified != self.modified {
+                self.modified = modified;
+                info!(
+                    "🔄 {} changed, reloading config",
+                    self.source.path.display()
+                );
+                return true;
+            }
+
+            if Instant::now() >= deadline {
+                return false;
+            }
+        }
+    }
+
+    /// Reads and validates the config again, never prompting for, creating
+    /// or writing the file.
+    pub fn reload(&mut self) -> Result<Config, Box<dyn Error>> {
+        if !self.source.path.exists() {
+            return Err(format!("'{}' no longer exists", self.source.path.display()).into());
+        }
+
+        self.source.read()
+    }
+
+    #[cfg(unix)]
+    async fn hangup(&mut self) {
+        match self.hangup.as_mut() {
+            Some(hangup) => {
+                hangup.recv().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn hangup(&mut self) {
+        std::future::pending().await
+    }
+}
+
+fn modified_at(source: &ConfigSource) -> Option<SystemTime> {
+    let modified = fs::metadata(&source.path).and_then(|m| m.modified());
+    if let Err(e) = &modified {
+        debug!("Can't read {}: {}", source.path.display(), e);
+    }
+    modified.ok()
+}
+
+/// Lists the settings that differ between two configs as
+/// `section.key: old → new`. Secrets are only named, never shown.
+pub fn config_diff(old: &Config, new: &Config) -> Result<Vec<String>, Box<dyn Error>> {
+    let old = flatten(&toml::Value::try_from(old)?);
+    let new = flatten(&toml::Value::try_from(new)?);
+
+    let mut changes = Vec::new();
+    for (key, old_value) in &old {
+        match new.get(key) {
+            Some(new_value) if new_value == old_value => {}
+            Some(_) if SECRET_KEYS.contains(&key.as_str()) => {
+                changes.push(format!("{}: changed", key))
+            }
+            Some(new_value) => changes.push(format!("{}: {} → {}", key, old_value, new_value)),
+            None => changes.push(format!("{}: {} → (unset)", key, old_value)),
+        }
+    }
+    for (key, new_value) in &new {
+        if !old.contains_key(key) {
+            changes.push(format!("{}: (unset) → {}", key, new_value));
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Flattens tables into dotted keys. Arrays are compared as a whole.
+fn flatten(value: &toml::Value) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, value: &toml::Value, out: &mut BTreeMap<String, String>) {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, value) in table {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&path, value, out);
+                }
+            }
+            value => {
+                out.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk("", value, &mut out);
+    out
+}
@@ -0,0 +1,361 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`)
+/// evaluated in a fixed timezone.
+///
+/// Each field accepts `*`, single values, ranges (`1-5`), lists (`1,15`) and
+/// steps (`*/15`, `0-30/10`). Days of week run from 0 (Sunday) to 7 (Sunday).
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+    timezone: Tz,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str, timezone: Tz) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {} in '{}'",
+                fields.len(),
+                expression
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "day of week")?;
+        // Both 0 and 7 mean Sunday
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, "day of month")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            days_of_week,
+            day_of_month_restricted: fields[2] != "*",
+            day_of_week_restricted: fields[4] != "*",
+            timezone,
+        })
+    }
+
+    /// Returns the first matching minute strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = after.with_timezone(&self.timezone);
+        let start_date = local.date_naive();
+
+        // Five years covers every valid combination, including Feb 29th
+        for day_offset in 0..(366 * 5) {
+            let date = start_date + Duration::days(day_offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+
+            for hour in (0..24u32).filter(|&h| self.hours[h as usize]) {
+                for minute in (0..60u32).filter(|&m| self.minutes[m as usize]) {
+                    let Some(naive) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    // Skip times that don't exist due to DST transitions
+                    let Some(candidate) = self.timezone.from_local_datetime(&naive).earliest()
+                    else {
+                        continue;
+                    };
+                    let candidate = candidate.with_timezone(&Utc);
+                    if candidate > after {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !self.months[date.month() as usize] {
+            return false;
+        }
+
+        let dom = self.days_of_month[date.day() as usize];
+        let dow = self.days_of_week[date.weekday().num_days_from_sunday() as usize];
+
+        // Standard cron: when both day fields are restricted, either may match
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+/// Parses one cron field into a lookup table indexed by value.
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+    let parse = |value: &str| {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|v| (min..=max).contains(v))
+            .ok_or_else(|| format!("invalid {} '{}' (expected {}-{})", name, value, min, max))
+    };
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step '{}' in {}", step, name))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse(start)?, parse(end)?)
+        } else {
+            let value = parse(range)?;
+            // "5/15" means "from 5 to the end, every 15"
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start > end {
+            return Err(format!("invalid {} range '{}'", name, range));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+/// A daily time-of-day window in a fixed timezone. Windows whose end is
+/// before their start wrap past midnight (e.g. `23:00`-`07:00`).
+#[derive(Debug, Clone)]
+pub struct QuietWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+    timezone: Tz,
+}
+
+impl QuietWindow {
+    pub fn parse(start: &str, end: &str, timezone: &str) -> Result<Self, String> {
+        let parse_time = |value: &str| {
+            NaiveTime::parse_from_str(value, "%H:%M")
+                .map_err(|_| format!("invalid time '{}' (expected HH:MM)", value))
+        };
+
+        Ok(Self {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+            timezone: timezone
+                .parse::<Tz>()
+                .map_err(|_| format!("unknown timezone '{}'", timezone))?,
+        })
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let time = at.with_timezone(&self.timezone).time();
+
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn next(expression: &str, timezone: Tz, after: &str) -> DateTime<Utc> {
+        Schedule::parse(expression, timezone)
+            .unwrap()
+            .next_after(utc(after))
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_values_lists_and_wildcards() {
+        let field = parse_field("1,15,30", 0, 59, "minute").unwrap();
+        let set: Vec<usize> = (0..60).filter(|&m| field[m]).collect();
+        assert_eq!(set, vec![1, 15, 30]);
+
+        assert!(parse_field("*", 0, 23, "hour").unwrap().iter().all(|&v| v));
+    }
+
+    #[test]
+    fn parses_ranges_and_steps() {
+        let values = |field: &str, min, max| {
+            let allowed = parse_field(field, min, max, "field").unwrap();
+            (0..allowed.len())
+                .filter(|&v| allowed[v])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values("1-5", 0, 59), vec![1, 2, 3, 4, 5]);
+        assert_eq!(values("*/15", 0, 59), vec![0, 15, 30, 45]);
+        assert_eq!(values("0-30/10", 0, 59), vec![0, 10, 20, 30]);
+        assert_eq!(values("5/20", 0, 59), vec![5, 25, 45]);
+        assert_eq!(values("1-3,20-22/2", 0, 23), vec![1, 2, 3, 20, 22]);
+    }
+
+    #[test]
+    fn sunday_is_both_zero_and_seven() {
+        // 2026-10-18 is a Sunday
+        let after = "2026-10-14T12:00:00Z";
+        let expected = utc("2026-10-18T09:00:00Z");
+        assert_eq!(next("0 9 * * 0", Tz::UTC, after), expected);
+        assert_eq!(next("0 9 * * 7", Tz::UTC, after), expected);
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or any Monday, whichever comes first
+        let schedule = "0 0 1 * 1";
+        assert_eq!(
+            next(schedule, Tz::UTC, "2026-10-14T12:00:00Z"),
+            utc("2026-10-19T00:00:00Z")
+        );
+        assert_eq!(
+            next(schedule, Tz::UTC, "2026-10-27T12:00:00Z"),
+            utc("2026-11-01T00:00:00Z")
+        );
+
+        // Only the day of month is restricted
+        assert_eq!(
+            next("0 0 13 * *", Tz::UTC, "2026-10-14T12:00:00Z"),
+            utc("2026-11-13T00:00:00Z")
+        );
+        // Only the day of week is restricted (Friday)
+        assert_eq!(
+            next("0 0 * * 5", Tz::UTC, "2026-10-14T12:00:00Z"),
+            utc("2026-10-16T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn next_after_is_strictly_later() {
+        assert_eq!(
+            next("30 8 * * *", Tz::UTC, "2026-10-14T08:30:00Z"),
+            utc("2026-10-15T08:30:00Z")
+        );
+        assert_eq!(
+            next("*/5 * * * *", Tz::UTC, "2026-10-14T08:31:10Z"),
+            utc("2026-10-14T08:35:00Z")
+        );
+    }
+
+    #[test]
+    fn finds_leap_days() {
+        assert_eq!(
+            next("0 12 29 2 *", Tz::UTC, "2026-03-01T00:00:00Z"),
+            utc("2028-02-29T12:00:00Z")
+        );
+    }
+
+    #[test]
+    fn runs_in_local_time() {
+        // 09:00 in Berlin is 07:00 UTC in summer and 08:00 UTC in winter
+        let berlin = Tz::Europe__Berlin;
+        assert_eq!(
+            next("0 9 * * *", berlin, "2026-07-01T12:00:00Z"),
+            utc("2026-07-02T07:00:00Z")
+        );
+        assert_eq!(
+            next("0 9 * * *", berlin, "2026-12-01T12:00:00Z"),
+            utc("2026-12-02T08:00:00Z")
+        );
+    }
+
+    #[test]
+    fn skips_times_missing_after_spring_forward() {
+        // Berlin jumps from 02:00 to 03:00 on 2026-03-29
+        let berlin = Tz::Europe__Berlin;
+        assert_eq!(
+            next("30 2 * * *", berlin, "2026-03-28T12:00:00Z"),
+            utc("2026-03-30T00:30:00Z")
+        );
+        // The hour after the gap still runs that day
+        assert_eq!(
+            next("30 3 * * *", berlin, "2026-03-28T12:00:00Z"),
+            utc("2026-03-29T01:30:00Z")
+        );
+    }
+
+    #[test]
+    fn runs_once_when_fall_back_repeats_a_time() {
+        // Berlin repeats 02:00-03:00 on 2026-10-25; only the first one runs
+        let berlin = Tz::Europe__Berlin;
+        let first = next("30 2 * * *", berlin, "2026-10-24T12:00:00Z");
+        assert_eq!(first, utc("2026-10-25T00:30:00Z"));
+        assert_eq!(
+            next("30 2 * * *", berlin, "2026-10-25T00:30:00Z"),
+            utc("2026-10-26T01:30:00Z")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "0 9 * *",
+            "0 9 * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "30-10 * * * *",
+            "a * * * *",
+            "1,,2 * * * *",
+            "-1 * * * *",
+        ] {
+            assert!(
+                Schedule::parse(expression, Tz::UTC).is_err(),
+                "'{}' should be rejected",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn impossible_dates_never_run() {
+        let schedule = Schedule::parse("0 0 31 2 *", Tz::UTC).unwrap();
+        assert_eq!(schedule.next_after(utc("2026-01-01T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn quiet_windows_wrap_past_midnight() {
+        let window = QuietWindow::parse("23:00", "07:00", "UTC").unwrap();
+        assert!(window.contains(utc("2026-10-14T23:30:00Z")));
+        assert!(window.contains(utc("2026-10-14T06:59:00Z")));
+        assert!(!window.contains(utc("2026-10-14T07:00:00Z")));
+        assert!(!window.contains(utc("2026-10-14T12:00:00Z")));
+
+        assert!(QuietWindow::parse("25:00", "07:00", "UTC").is_err());
+        assert!(QuietWindow::parse("23:00", "07:00", "Mars/Olympus").is_err());
+    }
+}
@@ -1,32 +1,185 @@
-use crate::core::Config;
-use crate::services::{CommitScraper, Database, DiscordNotifier};
-use log::{debug, error, info};
+use crate::core::{Config, ConfigWatcher, GapAction, Schedule, config_diff};
+use crate::models::{BranchCommit, CommitInfo, DigestPeriod};
+use crate::services::{
+    CommitResult, CommitScraper, Database, DiscordNotifier, SentMessage, WebhookError, archive,
+    backup, gaps,
+};
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
 use std::error::Error;
+use std::path::Path;
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::time::{Instant, sleep_until};
 
 pub struct CommitTracker {
     config: Config,
     scraper: CommitScraper,
     notifier: DiscordNotifier,
     database: Database,
+    digests: Vec<DigestJob>,
+    bursts: Vec<PendingBurst>,
+    next_prune: Instant,
+    next_gap_scan: Instant,
+    backup: Option<BackupJob>,
+    instance_id: String,
+    active: bool,
+    watcher: Option<ConfigWatcher>,
+}
+
+/// Highest commit ID below which gaps have already been checked.
+const GAP_WATERMARK: &str = "gap_watermark";
+
+/// Commits whose authors are welcomed once quiet hours are over, as a
+/// comma-separated list of IDs.
+const DEFERRED_WELCOMES: &str = "deferred_welcomes";
+
+/// Lease held by the instance that polls and posts.
+pub const TRACKER_LEASE: &str = "tracker";
+
+/// How long a claim may sit unfinished before another instance may take the
+/// commit over, e.g. after a crash mid-post.
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How many times a commit is posted before its delivery is given up on.
+/// Given-up commits stay `failed` and can be posted with `replay`.
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+
+/// How often the retention rules are applied.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Commits from one author on one branch waiting to be posted together.
+struct PendingBurst {
+    commits: Vec<CommitResult>,
+    deliver_at: Instant,
+}
+
+impl PendingBurst {
+    fn accepts(&self, commit: &CommitInfo) -> bool {
+        let first = &self.commits[0].commit;
+        first.author() == commit.author() && first.branch == commit.branch
+    }
+
+    fn contains(&self, commit_id: i32) -> bool {
+        self.commits.iter().any(|r| r.commit.id == commit_id)
+    }
+}
+
+struct DigestJob {
+    period: DigestPeriod,
+    schedule: Schedule,
+    next_run: Option<DateTime<Utc>>,
+}
+
+struct BackupJob {
+    schedule: Schedule,
+    next_run: Option<DateTime<Utc>>,
 }
 
 impl CommitTracker {
-    pub async fn new() -> Result<Self, Box<dyn Error>> {
-        let config = Config::load_or_create()?;
-        let scraper = CommitScraper::new();
-        let notifier = DiscordNotifier::new(config.clone());
-        let database = Database::new(&config.database.url).await?;
+    pub async fn new(config: Config) -> Result<Self, Box<dyn Error>> {
+        Self::with_scraper(config, CommitScraper::new()?).await
+    }
+
+    /// A tracker fetching through `scraper` and posting through its HTTP
+    /// client, so trackers for several profiles can share both.
+    pub async fn with_scraper(
+        config: Config,
+        scraper: CommitScraper,
+    ) -> Result<Self, Box<dyn Error>> {
+        let notifier = DiscordNotifier::with_client(config.clone(), scraper.client());
+        let database = Database::new(config.database.url.expose()).await?;
+        let digests = Self::digest_jobs(&config);
+        let backup = Self::backup_job(&config);
+        let instance_id = config.lease.instance_id();
 
         Ok(Self {
             config,
             scraper,
             notifier,
             database,
+            digests,
+            bursts: Vec::new(),
+            next_prune: Instant::now(),
+            next_gap_scan: Instant::now(),
+            backup,
+            instance_id,
+            active: false,
+            watcher: None,
         })
     }
 
+    /// Reloads the config whenever the watcher asks for it.
+    pub fn with_watcher(mut self, watcher: ConfigWatcher) -> Self {
+        self.watcher = Some(watcher);
+        self
+    }
+
+    fn backup_job(config: &Config) -> Option<BackupJob> {
+        if !config.backup.enabled {
+            return None;
+        }
+
+        let schedule = config
+            .backup
+            .timezone()
+            .and_then(|timezone| Schedule::parse(&config.backup.schedule, timezone));
+        match schedule {
+            Ok(schedule) => {
+                let next_run = schedule.next_after(Utc::now());
+                if let Some(next_run) = next_run {
+                    info!("💾 Database backup scheduled for {}", next_run);
+                }
+                Some(BackupJob { schedule, next_run })
+            }
+            Err(e) => {
+                warn!("⚠️  Backups disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    fn digest_jobs(config: &Config) -> Vec<DigestJob> {
+        if !config.digest.enabled {
+            return Vec::new();
+        }
+
+        let timezone = match config.digest.timezone() {
+            Ok(timezone) => timezone,
+            Err(e) => {
+                warn!("⚠️  Digests disabled: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let now = Utc::now();
+        [
+            (DigestPeriod::Daily, &config.digest.daily_schedule),
+            (DigestPeriod::Weekly, &config.digest.weekly_schedule),
+        ]
+        .into_iter()
+        .filter(|(_, expression)| !expression.trim().is_empty())
+        .filter_map(
+            |(period, expression)| match Schedule::parse(expression, timezone) {
+                Ok(schedule) => {
+                    let next_run = schedule.next_after(now);
+                    if let Some(next_run) = next_run {
+                        info!("📰 {} digest scheduled for {}", period.label(), next_run);
+                    }
+                    Some(DigestJob {
+                        period,
+                        schedule,
+                        next_run,
+                    })
+                }
+                Err(e) => {
+                    warn!("⚠️  {} digest disabled: {}", period.label(), e);
+                    None
+                }
+            },
+        )
+        .collect()
+    }
+
     pub async fn start(&mut self) -> Result<(), Box<dyn Error>> {
         info!(
             "🚀 {} started - monitoring Facepunch commits",
@@ -43,62 +196,1340 @@ impl CommitTracker {
             info!("📊 No previous commits found in database - starting fresh");
         }
 
+        tokio::select! {
+            result = self.poll_forever() => return result,
+            _ = shutdown_signal() => {}
+        }
+
+        // Held bursts are stored and picked up again by whoever polls next
+        info!("👋 Shutting down");
+        if self.active {
+            self.release_lease().await?;
+        }
+        Ok(())
+    }
+
+    async fn poll_forever(&mut self) -> Result<(), Box<dyn Error>> {
         loop {
+            if !self.hold_lease().await {
+                self.pause(Self::next_poll).await;
+                continue;
+            }
+
+            if let Err(e) = self.flush_deferred_commits().await {
+                error!("❌ Failed to deliver deferred commits: {}", e);
+            }
+
+            if let Err(e) = self.restore_bursts().await {
+                error!("❌ Failed to restore held bursts: {}", e);
+            }
+
+            if let Err(e) = self.retry_undelivered().await {
+                error!("❌ Retrying failed deliveries: {}", e);
+            }
+
             if let Err(e) = self.check_for_new_commits().await {
                 error!("❌ {}", e);
             }
 
-            sleep(Duration::from_secs(
-                self.config.monitoring.check_interval_secs,
-            ))
-            .await;
+            if let Err(e) = self.flush_bursts().await {
+                error!("❌ {}", e);
+            }
+
+            if let Err(e) = self.scan_gaps_if_due().await {
+                error!("❌ Gap scan failed: {}", e);
+            }
+
+            self.run_due_digests().await;
+
+            if let Err(e) = self.prune_if_due().await {
+                error!("❌ Failed to prune old commits: {}", e);
+            }
+
+            self.run_due_backup().await;
+
+            self.pause(Self::next_wake).await;
         }
     }
 
-    async fn check_for_new_commits(&mut self) -> Result<(), Box<dyn Error>> {
-        let result = self
-            .scraper
-            .fetch_latest_commit(&self.config.monitoring.commits_url)
+    /// Sleeps until `wake_at` says, given when the pause started, reloading
+    /// the config whenever it changes in the meantime. The wake-up time is
+    /// worked out again after each reload, so a new interval applies to the
+    /// current pause.
+    async fn pause(&mut self, wake_at: fn(&Self, Instant) -> Instant) {
+        let since = Instant::now();
+        let mut deadline = wake_at(self, since);
+
+        while let Some(watcher) = self.watcher.as_mut() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !watcher.wait(remaining).await {
+                return;
+            }
+            self.reload_config();
+            deadline = wake_at(self, since);
+        }
+
+        sleep_until(deadline).await;
+    }
+
+    /// Swaps in the reloaded config together with everything built from it.
+    /// An invalid config is rejected and the current one kept.
+    fn reload_config(&mut self) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+        let mut config = match watcher.reload() {
+            Ok(config) => config,
+            Err(e) => {
+                error!("❌ Config reload failed, keeping the current config: {}", e);
+                return;
+            }
+        };
+
+        // These are only read at startup
+        if config.database.url != self.config.database.url {
+            warn!("⚠️  database.url changes take effect after a restart");
+            config.database.url = self.config.database.url.clone();
+        }
+        if config.lease.instance_id != self.config.lease.instance_id {
+            warn!("⚠️  lease.instance_id changes take effect after a restart");
+            config.lease.instance_id = self.config.lease.instance_id.clone();
+        }
+
+        let changes = match config_diff(&self.config, &config) {
+            Ok(changes) => changes,
+            Err(e) => {
+                error!("❌ Config reload failed, keeping the current config: {}", e);
+                return;
+            }
+        };
+        if changes.is_empty() {
+            info!("🔄 Config reloaded, nothing changed");
+            return;
+        }
+
+        info!("🔄 Config reloaded with {} change(s):", changes.len());
+        for change in &changes {
+            info!("   {}", change);
+        }
+
+        let changed = |section: &str| {
+            changes
+                .iter()
+                .any(|change| change.starts_with(&format!("{}.", section)))
+        };
+        if changed("digest") {
+            self.digests = Self::digest_jobs(&config);
+        }
+        if changed("backup") {
+            self.backup = Self::backup_job(&config);
+        }
+        self.notifier = DiscordNotifier::with_client(config.clone(), self.scraper.client());
+        self.config = config;
+    }
+
+    /// Polls the feed once: delivers deferred commits, posts anything new
+    /// without waiting for bursts to fill up, and checks for gaps if enabled.
+    /// Fails if another instance holds the lease.
+    pub async fn run_once(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.hold_lease().await {
+            let holder = self.database.lease_holder(TRACKER_LEASE).await?;
+            return Err(format!(
+                "{} is active; stop it first or disable the lease",
+                holder.as_deref().unwrap_or("Another instance")
+            )
+            .into());
+        }
+
+        let result = self.poll_once().await;
+        // Let a long-running instance take over right away
+        self.release_lease().await?;
+        result
+    }
+
+    async fn poll_once(&mut self) -> Result<(), Box<dyn Error>> {
+        self.flush_deferred_commits().await?;
+        self.restore_bursts().await?;
+        self.retry_undelivered().await?;
+        self.check_for_new_commits().await?;
+
+        let now = Instant::now();
+        for burst in &mut self.bursts {
+            burst.deliver_at = now;
+        }
+        self.flush_bursts().await?;
+
+        self.scan_gaps_if_due().await
+    }
+
+    /// Takes or renews the lease that makes this the active instance. Other
+    /// instances stand by until the lease expires.
+    async fn hold_lease(&mut self) -> bool {
+        if !self.config.lease.enabled {
+            return true;
+        }
+
+        let ttl = Duration::from_secs(self.config.lease.ttl_secs);
+        let acquired = match self
+            .database
+            .acquire_lease(TRACKER_LEASE, &self.instance_id, ttl)
+            .await
+        {
+            Ok(acquired) => acquired,
+            Err(e) => {
+                error!("❌ Failed to renew lease: {}", e);
+                false
+            }
+        };
+
+        if acquired && !self.active {
+            info!(
+                "🔑 {} acquired the lease and is now active",
+                self.instance_id
+            );
+        } else if !acquired && self.active {
+            warn!("⚠️  {} lost the lease - standing by", self.instance_id);
+        } else if !acquired {
+            let holder = self
+                .database
+                .lease_holder(TRACKER_LEASE)
+                .await
+                .ok()
+                .flatten();
+            debug!(
+                "Standing by, lease held by {}",
+                holder.as_deref().unwrap_or("another instance")
+            );
+        }
+
+        self.active = acquired;
+        acquired
+    }
+
+    /// Gives up the lease so a standby instance takes over without waiting
+    /// for it to expire.
+    async fn release_lease(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.config.lease.enabled {
+            return Ok(());
+        }
+
+        self.database
+            .release_lease(TRACKER_LEASE, &self.instance_id)
             .await?;
-        let commit = &result.commit;
+        self.active = false;
+        info!("🔑 {} released the lease", self.instance_id);
+        Ok(())
+    }
+
+    /// Claims commits for this instance, dropping any that another instance
+    /// is already posting.
+    async fn claim(&self, results: &[CommitResult]) -> Result<Vec<CommitResult>, Box<dyn Error>> {
+        let destination = self.notifier.destination();
+        let mut claimed = Vec::with_capacity(results.len());
+
+        for result in results {
+            if self
+                .database
+                .claim_commit(
+                    result.commit.id,
+                    destination,
+                    &self.instance_id,
+                    CLAIM_TIMEOUT,
+                )
+                .await?
+            {
+                claimed.push(result.clone());
+            } else {
+                debug!(
+                    "Commit #{} is handled by another instance",
+                    result.commit.id
+                );
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    /// Delivers commits and welcomes held back during quiet hours once they
+    /// are over.
+    async fn flush_deferred_commits(&self) -> Result<(), Box<dyn Error>> {
+        if self.notifier.in_quiet_hours(Utc::now()) {
+            return Ok(());
+        }
+
+        let destination = self.notifier.destination();
+        let commits = self.database.get_deferred_commits(destination).await?;
+        if commits.is_empty() {
+            return self.send_deferred_welcomes().await;
+        }
+
+        let sent = match self.notifier.send_deferred_summary(&commits).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                self.record_failure(commits.iter(), e.as_ref()).await;
+                return Err(e);
+            }
+        };
+
+        for commit in &commits {
+            self.database
+                .mark_commit_sent(commit.id, destination, Some(&sent.id), Some(sent.status))
+                .await?;
+        }
+
+        info!(
+            "🌅 Delivered {} commits deferred during quiet hours",
+            commits.len()
+        );
+        self.send_deferred_welcomes().await
+    }
 
-        // Check if we've already sent this commit
-        if self.database.is_commit_sent(commit.id).await? {
-            debug!("Commit #{} already sent, skipping", commit.id);
+    /// Welcomes the new contributors whose first commit came in during quiet
+    /// hours. Like any welcome, one that fails isn't retried.
+    async fn send_deferred_welcomes(&self) -> Result<(), Box<dyn Error>> {
+        let commit_ids = self.deferred_welcomes().await?;
+        if commit_ids.is_empty() {
             return Ok(());
         }
+        self.database.set_state(DEFERRED_WELCOMES, "").await?;
+
+        if !self.config.authors.announce_new {
+            return Ok(());
+        }
+        for record in self.database.get_commit_records(&commit_ids).await? {
+            if let Err(e) = self.notifier.send_new_contributor(&record.commit).await {
+                warn!("⚠️  Failed to announce new contributor: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn deferred_welcomes(&self) -> Result<Vec<i32>, Box<dyn Error>> {
+        let value = self.database.get_state(DEFERRED_WELCOMES).await?;
+        Ok(value
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.parse().ok())
+            .collect())
+    }
+
+    /// When a poll that waited since `since` is due.
+    fn next_poll(&self, since: Instant) -> Instant {
+        since + Duration::from_secs(self.config.monitoring.check_interval_secs)
+    }
+
+    /// The next poll, or earlier if a digest, backup or burst is due sooner.
+    fn next_wake(&self, since: Instant) -> Instant {
+        let now = Utc::now();
+        let instant = Instant::now();
+
+        let digests = self
+            .digests
+            .iter()
+            .filter_map(|job| job.next_run)
+            .chain(self.backup.as_ref().and_then(|job| job.next_run))
+            .map(|next_run| instant + (next_run - now).to_std().unwrap_or_default());
+        let bursts = self.bursts.iter().map(|burst| burst.deliver_at);
+
+        digests
+            .chain(bursts)
+            .fold(self.next_poll(since), Instant::min)
+    }
+
+    async fn run_due_digests(&mut self) {
+        let now = Utc::now();
+        let due: Vec<usize> = (0..self.digests.len())
+            .filter(|&i| self.digests[i].next_run.is_some_and(|next| next <= now))
+            .collect();
+
+        if due.is_empty() {
+            return;
+        }
+
+        // Likes accumulate after a commit is posted, so refresh them first
+        match self
+            .scraper
+            .fetch_commits(&self.config.monitoring.commits_url)
+            .await
+        {
+            Ok(response) => {
+                if let Err(e) = self.database.update_reactions(&response.results).await {
+                    warn!("⚠️  Failed to refresh commit reactions: {}", e);
+                }
+            }
+            Err(e) => warn!("⚠️  Failed to refresh commit reactions: {}", e),
+        }
+
+        for i in due {
+            let job = &self.digests[i];
+            let until = job.next_run.unwrap_or(now);
+
+            if let Err(e) = self.send_digest(job.period, until).await {
+                error!("❌ Failed to send {} digest: {}", job.period.label(), e);
+            }
+
+            let job = &mut self.digests[i];
+            job.next_run = job.schedule.next_after(now);
+        }
+    }
+
+    async fn run_due_backup(&mut self) {
+        let now = Utc::now();
+        let Some(job) = self.backup.as_mut() else {
+            return;
+        };
+        if job.next_run.is_none_or(|next| next > now) {
+            return;
+        }
+        job.next_run = job.schedule.next_after(now);
+
+        let config = &self.config.backup;
+        match backup::create_backup(
+            &self.database,
+            self.config.database.url.expose(),
+            Path::new(&config.directory),
+            config.keep,
+        )
+        .await
+        {
+            Ok(path) => info!("💾 Backed up database to {}", path.display()),
+            Err(e) => error!("❌ Database backup failed: {}", e),
+        }
+    }
+
+    async fn send_digest(
+        &self,
+        period: DigestPeriod,
+        until: DateTime<Utc>,
+    ) -> Result<(), Box<dyn Error>> {
+        let report = self
+            .database
+            .get_digest_report(
+                period,
+                until,
+                self.config.digest.top_authors,
+                self.config.digest.top_commits,
+            )
+            .await?;
+
+        self.notifier.send_digest(&report).await?;
 
         info!(
-            "🆕 New commit #{} by {} - {}",
-            commit.id,
-            commit.author(),
-            commit.message
+            "📰 Sent {} digest ({} commits)",
+            period.label(),
+            report.total_commits
         );
+        Ok(())
+    }
+
+    async fn check_for_new_commits(&mut self) -> Result<(), Box<dyn Error>> {
+        let results = self
+            .scraper
+            .fetch_latest_commits(&self.config.monitoring.commits_url)
+            .await?;
+        let last_sent_id = self.database.get_last_sent_commit_id().await?;
+
+        // Oldest first, and only commits newer than anything we've handled
+        let mut new_commits = Vec::new();
+        for result in results.into_iter().rev() {
+            let commit = &result.commit;
+            if last_sent_id.is_some_and(|last| commit.id <= last)
+                || self.bursts.iter().any(|burst| burst.contains(commit.id))
+                || self
+                    .database
+                    .is_commit_sent(commit.id, self.notifier.destination())
+                    .await?
+            {
+                debug!("Commit #{} already sent, skipping", commit.id);
+                continue;
+            }
+            new_commits.push(result);
+        }
 
-        // Send notification
-        self.notifier.send_commit_notification(&result).await?;
+        // On a fresh database only announce the latest commit, not the whole page
+        if last_sent_id.is_none() && new_commits.len() > 1 {
+            new_commits.drain(..new_commits.len() - 1);
+        }
+
+        if new_commits.is_empty() {
+            return Ok(());
+        }
+
+        // Everyone is new to a fresh database, so don't welcome anyone yet
+        for result in &new_commits {
+            self.record_commit(&result.commit, last_sent_id.is_some())
+                .await?;
+        }
+
+        if self.config.bursts.enabled {
+            let window = Duration::from_secs(self.config.bursts.window_secs);
+            for result in new_commits {
+                self.hold_in_burst(result, Instant::now() + window).await?;
+            }
+            return Ok(());
+        }
+
+        self.deliver_all(self.group_duplicates(new_commits)).await
+    }
+
+    /// Tries again to post commits whose last attempt failed, and commits an
+    /// instance claimed but never finished posting. Each commit gets at most
+    /// `MAX_DELIVERY_ATTEMPTS` attempts.
+    async fn retry_undelivered(&mut self) -> Result<(), Box<dyn Error>> {
+        let stale_before = Utc::now() - chrono::Duration::from_std(CLAIM_TIMEOUT)?;
+        let commits = self
+            .database
+            .get_undelivered_commits(
+                self.notifier.destination(),
+                MAX_DELIVERY_ATTEMPTS,
+                stale_before,
+            )
+            .await?;
+
+        let retries: Vec<CommitResult> = commits
+            .into_iter()
+            .filter(|commit| !self.bursts.iter().any(|burst| burst.contains(commit.id)))
+            .map(CommitResult::stored)
+            .collect();
+        if retries.is_empty() {
+            return Ok(());
+        }
+        info!("🔁 Retrying {} undelivered commits", retries.len());
+
+        // Regroup them the way they'd have been posted in the first place
+        if self.config.bursts.enabled {
+            for result in retries {
+                self.hold_in_burst(result, Instant::now()).await?;
+            }
+            return Ok(());
+        }
+
+        self.deliver_all(self.group_duplicates(retries)).await
+    }
+
+    /// Looks for commits missed while the tracker was down. Only IDs above the
+    /// stored watermark are checked, so each gap is looked at once; gaps older
+    /// than the configured pages can't be recovered and are only reported.
+    async fn scan_gaps_if_due(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.config.gaps.enabled || Instant::now() < self.next_gap_scan {
+            return Ok(());
+        }
+        self.next_gap_scan = Instant::now() + Duration::from_secs(self.config.gaps.interval_secs);
+
+        let Some(latest_id) = self.database.get_latest_commit_id().await? else {
+            return Ok(());
+        };
+        let watermark = self.database.get_state(GAP_WATERMARK).await?;
+        let Some(watermark) = watermark.and_then(|value| value.parse().ok()) else {
+            // Start from here rather than digging through all of history
+            return self
+                .database
+                .set_state(GAP_WATERMARK, &latest_id.to_string())
+                .await;
+        };
+
+        let gaps = self.database.find_commit_gaps(watermark).await?;
+        if !gaps.is_empty() {
+            let scan = gaps::resolve_gaps(
+                &self.scraper,
+                &self.config.monitoring.commits_url,
+                &gaps,
+                self.config.gaps.max_pages,
+            )
+            .await?;
+
+            info!(
+                "🕳️  Checked {} gaps in commit IDs: {} missed, {} hidden",
+                gaps.len(),
+                scan.missed.len(),
+                scan.hidden
+            );
+            for gap in &scan.unresolved {
+                warn!(
+                    "⚠️  Commits #{}-#{} are older than the {} pages searched and can't be recovered",
+                    gap.first, gap.last, self.config.gaps.max_pages
+                );
+            }
+
+            self.handle_missed_commits(scan.missed).await?;
+        }
 
-        // Mark as sent in database
         self.database
-            .mark_commit_sent(
-                commit.id,
-                &commit.author(),
-                &commit.message,
-                &commit.branch,
-                &commit.changeset,
+            .set_state(GAP_WATERMARK, &latest_id.to_string())
+            .await
+    }
+
+    async fn handle_missed_commits(&self, missed: Vec<CommitResult>) -> Result<(), Box<dyn Error>> {
+        let destination = self.notifier.destination();
+        for result in &missed {
+            self.record_commit(&result.commit, true).await?;
+        }
+
+        match self.config.gaps.action {
+            GapAction::Deliver => self.deliver_all(self.group_duplicates(missed)).await?,
+            GapAction::Report => {
+                for result in &missed {
+                    let commit = &result.commit;
+                    warn!(
+                        "⚠️  Missed commit #{} by {} on {}: {}",
+                        commit.id,
+                        commit.author(),
+                        commit.branch,
+                        commit.link(&self.config.monitoring.commits_url)
+                    );
+                    self.database
+                        .mark_commit_skipped(commit.id, destination, None)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores a commit and, if `welcome` is set, welcomes its author when
+    /// they've never committed before. A failed welcome doesn't hold up the
+    /// commit itself.
+    async fn record_commit(
+        &self,
+        commit: &CommitInfo,
+        welcome: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let new_author = self.database.record_commit(commit).await?;
+        if !new_author || !welcome || !self.config.authors.announce_new {
+            return Ok(());
+        }
+
+        info!("🎉 First commit by {}", commit.author());
+        if self.notifier.in_quiet_hours(Utc::now()) {
+            debug!("Welcoming {} once quiet hours end", commit.author());
+            let mut commit_ids = self.deferred_welcomes().await?;
+            commit_ids.push(commit.id);
+            let value = commit_ids
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            self.database.set_state(DEFERRED_WELCOMES, &value).await?;
+        } else if let Err(e) = self.notifier.send_new_contributor(commit).await {
+            warn!("⚠️  Failed to announce new contributor: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Applies the retention rules, archiving pruned commits first when an
+    /// archive directory is configured. Runs at most once per
+    /// `PRUNE_INTERVAL`.
+    async fn prune_if_due(&mut self) -> Result<(), Box<dyn Error>> {
+        if Instant::now() < self.next_prune {
+            return Ok(());
+        }
+        self.next_prune = Instant::now() + PRUNE_INTERVAL;
+
+        let retention = &self.config.database.retention;
+        let commit_ids = self
+            .database
+            .find_prunable_commits(retention.keep_last, retention)
+            .await?;
+        if commit_ids.is_empty() {
+            return Ok(());
+        }
+
+        if !retention.archive_dir.is_empty() {
+            let records = self.database.get_commit_records(&commit_ids).await?;
+            let path = archive::write_archive(Path::new(&retention.archive_dir), &records)?;
+            info!(
+                "🗄️  Archived {} commits to {}",
+                records.len(),
+                path.display()
+            );
+        }
+
+        let deleted = self.database.delete_commits(&commit_ids).await?;
+        info!("🧹 Pruned {} old commits", deleted);
+        Ok(())
+    }
+
+    /// Adds a commit to the open burst of its author and branch, or starts a
+    /// new burst due at `deliver_at`. The commit is recorded as pending so
+    /// the burst survives a restart.
+    async fn hold_in_burst(
+        &mut self,
+        result: CommitResult,
+        deliver_at: Instant,
+    ) -> Result<(), Box<dyn Error>> {
+        let commit_id = result.commit.id;
+        let deliver_at = match self.bursts.iter_mut().find(|b| b.accepts(&result.commit)) {
+            Some(burst) => {
+                burst.commits.push(result);
+                burst.deliver_at
+            }
+            None => {
+                self.bursts.push(PendingBurst {
+                    commits: vec![result],
+                    deliver_at,
+                });
+                deliver_at
+            }
+        };
+
+        let due = deliver_at.saturating_duration_since(Instant::now());
+        self.database
+            .mark_commit_pending(
+                commit_id,
+                self.notifier.destination(),
+                Utc::now() + chrono::Duration::from_std(due)?,
             )
+            .await
+    }
+
+    /// Picks up commits held in bursts that this instance doesn't know
+    /// about, e.g. after a restart or when taking over from another instance.
+    async fn restore_bursts(&mut self) -> Result<(), Box<dyn Error>> {
+        let pending = self
+            .database
+            .get_pending_commits(self.notifier.destination())
             .await?;
 
+        let now = Utc::now();
+        let mut restored = 0;
+        for (commit, deliver_at) in pending {
+            if self.bursts.iter().any(|burst| burst.contains(commit.id)) {
+                continue;
+            }
+            let due = (deliver_at - now).to_std().unwrap_or_default();
+            self.hold_in_burst(CommitResult::stored(commit), Instant::now() + due)
+                .await?;
+            restored += 1;
+        }
+
+        if restored > 0 {
+            info!("📦 Restored {} commits held in bursts", restored);
+        }
+        Ok(())
+    }
+
+    /// Delivers bursts whose grouping window has elapsed. A burst of a single
+    /// commit is posted like any other commit.
+    async fn flush_bursts(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = Instant::now();
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.bursts)
+            .into_iter()
+            .partition(|burst| burst.deliver_at <= now);
+        self.bursts = pending;
+
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        // Commits that aren't posted stay pending or failed in the database
+        // and are picked up again on the next poll
+        let mut first_error = None;
+        let mut singles = Vec::new();
+        for burst in due {
+            if burst.commits.len() == 1 {
+                singles.extend(burst.commits);
+            } else if let Err(e) = self.deliver_burst(&burst.commits).await {
+                report_error(&mut first_error, e);
+            }
+        }
+
+        // Lone commits from different branches may still be duplicates
+        singles.sort_by_key(|r| r.commit.id);
+        if let Err(e) = self.deliver_all(self.group_duplicates(singles)).await {
+            report_error(&mut first_error, e);
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Delivers each group, carrying on past failures so one bad post
+    /// doesn't hold up the rest. Failed commits are retried on a later poll.
+    /// Returns the first error.
+    async fn deliver_all(&self, groups: Vec<Vec<CommitResult>>) -> Result<(), Box<dyn Error>> {
+        let mut first_error = None;
+        for group in groups {
+            if let Err(e) = self.deliver(&group).await {
+                report_error(&mut first_error, e);
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+
+    async fn deliver_burst(&self, results: &[CommitResult]) -> Result<(), Box<dyn Error>> {
+        let results = &self.claim(results).await?;
+        if results.len() < 2 {
+            // Another instance took the rest; post what's left on its own
+            return self.deliver(results).await;
+        }
+
+        let first = &results[0].commit;
+        info!(
+            "📦 Burst of {} commits by {} on {}",
+            results.len(),
+            first.author(),
+            first.branch
+        );
+
+        if self.notifier.in_quiet_hours(Utc::now()) {
+            return self.defer(results).await;
+        }
+
+        let sent = match self.notifier.send_burst_notification(results).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                self.record_failure(results.iter().map(|r| &r.commit), e.as_ref())
+                    .await;
+                return Err(e);
+            }
+        };
+        self.mark_sent(results, &sent).await?;
+
         info!("✅ Sent to Discord and marked as sent");
+        Ok(())
+    }
 
-        // Periodic cleanup to prevent database from growing too large
-        let sent_count = self.database.get_sent_commits_count().await?;
-        if sent_count > self.config.database.cleanup_keep_last + 100 {
+    /// Groups commits that carry the same change on different branches within
+    /// the duplicate window, keeping the order in which each group first
+    /// appears.
+    fn group_duplicates(&self, commits: Vec<CommitResult>) -> Vec<Vec<CommitResult>> {
+        let mut groups: Vec<Vec<CommitResult>> = Vec::new();
+        let window_secs = self.config.duplicates.window_secs;
+
+        for result in commits {
+            let existing = self.config.duplicates.enabled.then(|| {
+                groups.iter_mut().find(|group| {
+                    let first = &group[0].commit;
+                    first.author() == result.commit.author()
+                        && first.message_key() == result.commit.message_key()
+                        && first.is_within(&result.commit, window_secs)
+                })
+            });
+
+            match existing.flatten() {
+                Some(group) => group.push(result),
+                None => groups.push(vec![result]),
+            }
+        }
+
+        groups
+    }
+
+    /// Delivers one commit, or several copies of the same change on different
+    /// branches, as a single post.
+    async fn deliver(&self, group: &[CommitResult]) -> Result<(), Box<dyn Error>> {
+        let group = &self.claim(group).await?;
+        if group.is_empty() {
+            return Ok(());
+        }
+
+        for result in group {
+            let commit = &result.commit;
+            info!(
+                "🆕 New commit #{} by {} on {} - {}",
+                commit.id,
+                commit.author(),
+                commit.branch,
+                commit.message
+            );
+        }
+
+        if self.notifier.in_quiet_hours(Utc::now()) {
+            return self.defer(group).await;
+        }
+
+        let primary = &group[0];
+        let destination = self.notifier.destination();
+
+        if self.config.duplicates.enabled
+            && let Some((message_id, mut branches)) = self
+                .database
+                .find_recent_duplicate(
+                    &primary.commit,
+                    destination,
+                    self.config.duplicates.window_secs,
+                )
+                .await?
+        {
+            if self.config.duplicates.edit_original {
+                branches.extend(group.iter().map(|r| BranchCommit::from(&r.commit)));
+                match self
+                    .notifier
+                    .edit_commit_notification(&message_id, primary, &branches)
+                    .await
+                {
+                    Ok(()) => info!("✏️  Added branch to existing post {}", message_id),
+                    Err(e) => {
+                        // The original post may have been deleted; post anew
+                        warn!("⚠️  Could not edit post {}: {}", message_id, e);
+                        return self.send_group(group).await;
+                    }
+                }
+                for result in group {
+                    self.database
+                        .mark_commit_sent(result.commit.id, destination, Some(&message_id), None)
+                        .await?;
+                }
+            } else {
+                info!("⏭️  Duplicate of post {}, not posting again", message_id);
+                for result in group {
+                    self.database
+                        .mark_commit_skipped(result.commit.id, destination, Some(&message_id))
+                        .await?;
+                }
+            }
+            return Ok(());
+        }
+
+        self.send_group(group).await
+    }
+
+    async fn send_group(&self, group: &[CommitResult]) -> Result<(), Box<dyn Error>> {
+        let (primary, rest) = group.split_first().ok_or("Empty commit group")?;
+        let also: Vec<CommitInfo> = rest.iter().map(|r| r.commit.clone()).collect();
+
+        let sent = match self.notifier.send_commit_notification(primary, &also).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                self.record_failure(group.iter().map(|r| &r.commit), e.as_ref())
+                    .await;
+                return Err(e);
+            }
+        };
+        self.mark_sent(group, &sent).await?;
+
+        info!("✅ Sent to Discord and marked as sent");
+        Ok(())
+    }
+
+    async fn mark_sent(
+        &self,
+        results: &[CommitResult],
+        sent: &SentMessage,
+    ) -> Result<(), Box<dyn Error>> {
+        for result in results {
+            self.database
+                .mark_commit_sent(
+                    result.commit.id,
+                    self.notifier.destination(),
+                    Some(&sent.id),
+                    Some(sent.status),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn defer(&self, results: &[CommitResult]) -> Result<(), Box<dyn Error>> {
+        for result in results {
             self.database
-                .cleanup_old_commits(self.config.database.cleanup_keep_last)
+                .defer_commit(result.commit.id, self.notifier.destination())
                 .await?;
         }
 
+        info!("🌙 Quiet hours - deferred until they end");
         Ok(())
     }
+
+    /// Records a failed attempt so it shows up in the delivery history. The
+    /// commits are retried on later polls until `MAX_DELIVERY_ATTEMPTS`.
+    async fn record_failure<'a>(
+        &self,
+        commits: impl Iterator<Item = &'a CommitInfo>,
+        error: &(dyn Error + 'static),
+    ) {
+        let status = error
+            .downcast_ref::<WebhookError>()
+            .map(|e| e.status.as_u16());
+
+        for commit in commits {
+            if let Err(e) = self
+                .database
+                .record_delivery_failure(
+                    commit.id,
+                    self.notifier.destination(),
+                    status,
+                    &error.to_string(),
+                )
+                .await
+            {
+                warn!("⚠️  Failed to record delivery failure: {}", e);
+            }
+        }
+    }
+}
+
+/// Keeps the first of several errors to return, logging the others.
+fn report_error(first_error: &mut Option<Box<dyn Error>>, error: Box<dyn Error>) {
+    match first_error {
+        Some(_) => error!("❌ {}", error),
+        None => *first_error = Some(error),
+    }
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM (`docker stop`, `systemctl stop`).
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("⚠️  Can't listen for SIGTERM: {}", e),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        warn!("⚠️  Can't listen for Ctrl-C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ConfigSource, QuietHours, Secret};
+    use serde_json::{Value, json};
+    use std::fs;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn commit(id: i32, author: &str, message: &str) -> Value {
+        json!({
+            "id": id,
+            "repo": "rust_reboot",
+            "branch": "main",
+            "changeset": format!("c{}", id),
+            "created": "2025-01-01T12:00:00",
+            "likes": 0,
+            "dislikes": 0,
+            "message": message,
+            "user": { "name": author, "avatar": "" },
+        })
+    }
+
+    /// Serves `commits`, newest first, as the feed.
+    async fn serve_feed(server: &MockServer, commits: Vec<Value>) {
+        Mock::given(method("GET"))
+            .and(path("/feed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "total": commits.len(),
+                "skip": 0,
+                "take": commits.len(),
+                "results": commits,
+            })))
+            .mount(server)
+            .await;
+    }
+
+    async fn accept_posts(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/webhook"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": "1" })))
+            .mount(server)
+            .await;
+    }
+
+    async fn posts(server: &MockServer) -> Vec<Value> {
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| request.method == wiremock::http::Method::POST)
+            .map(|request| request.body_json().unwrap())
+            .collect()
+    }
+
+    fn config(server: &MockServer, dir: &TempDir) -> Config {
+        let mut config = Config::default();
+        config.discord.webhook_url = Secret::new(format!("{}/webhook", server.uri()));
+        config.monitoring.commits_url = format!("{}/feed", server.uri());
+        config.database.url = Secret::new(format!(
+            "sqlite:{}",
+            dir.path().join("commits.db").display()
+        ));
+        config.lease.enabled = false;
+        config.authors.announce_new = false;
+        config
+    }
+
+    async fn statuses(tracker: &CommitTracker, commit_ids: &[i32]) -> Vec<String> {
+        tracker
+            .database
+            .get_commit_records(commit_ids)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| {
+                record
+                    .deliveries
+                    .first()
+                    .map(|delivery| delivery.status.clone())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn restores_held_bursts_after_a_restart() {
+        let server = MockServer::start().await;
+        let dir = TempDir::new().unwrap();
+        let mut config = config(&server, &dir);
+        config.bursts.enabled = true;
+        config.bursts.window_secs = 3600;
+        accept_posts(&server).await;
+
+        serve_feed(&server, vec![commit(1, "alistair", "First")]).await;
+        let mut tracker = CommitTracker::new(config.clone()).await.unwrap();
+        tracker.run_once().await.unwrap();
+
+        // Two commits are held in a burst when the tracker goes down
+        server.reset().await;
+        accept_posts(&server).await;
+        serve_feed(
+            &server,
+            vec![
+                commit(3, "alistair", "Third"),
+                commit(2, "alistair", "Second"),
+                commit(1, "alistair", "First"),
+            ],
+        )
+        .await;
+        tracker.check_for_new_commits().await.unwrap();
+        assert_eq!(statuses(&tracker, &[2, 3]).await, ["pending", "pending"]);
+        drop(tracker);
+
+        // A newer commit is delivered after the restart; the burst still is
+        server.reset().await;
+        accept_posts(&server).await;
+        serve_feed(
+            &server,
+            vec![
+                commit(4, "maurino", "Fourth"),
+                commit(3, "alistair", "Third"),
+                commit(2, "alistair", "Second"),
+                commit(1, "alistair", "First"),
+            ],
+        )
+        .await;
+        let mut tracker = CommitTracker::new(config).await.unwrap();
+        tracker.run_once().await.unwrap();
+
+        assert_eq!(posts(&server).await.len(), 2);
+        assert_eq!(
+            statuses(&tracker, &[1, 2, 3, 4]).await,
+            ["sent", "sent", "sent", "sent"]
+        );
+    }
+
+    #[tokio::test]
+    async fn welcomes_new_contributors_after_quiet_hours() {
+        let server = MockServer::start().await;
+        let dir = TempDir::new().unwrap();
+        let mut config = config(&server, &dir);
+        config.authors.announce_new = true;
+        accept_posts(&server).await;
+
+        serve_feed(&server, vec![commit(1, "alistair", "First")]).await;
+        let mut tracker = CommitTracker::new(config.clone()).await.unwrap();
+        tracker.run_once().await.unwrap();
+
+        // A newcomer's first commit arrives during quiet hours
+        server.reset().await;
+        accept_posts(&server).await;
+        serve_feed(
+            &server,
+            vec![
+                commit(2, "maurino", "Second"),
+                commit(1, "alistair", "First"),
+            ],
+        )
+        .await;
+        let now = Utc::now();
+        let mut quiet = config.clone();
+        quiet.discord.quiet_hours = vec![QuietHours {
+            start: (now - chrono::Duration::hours(1))
+                .format("%H:%M")
+                .to_string(),
+            end: (now + chrono::Duration::hours(1))
+                .format("%H:%M")
+                .to_string(),
+            timezone: "UTC".to_string(),
+            destination: None,
+        }];
+        let mut tracker = CommitTracker::new(quiet).await.unwrap();
+        tracker.run_once().await.unwrap();
+        assert!(posts(&server).await.is_empty());
+        assert_eq!(statuses(&tracker, &[2]).await, ["deferred"]);
+
+        // Both the commit and the welcome go out once they're over
+        let mut tracker = CommitTracker::new(config).await.unwrap();
+        tracker.run_once().await.unwrap();
+        let posts = posts(&server).await;
+        assert_eq!(posts.len(), 2);
+        assert!(posts[1].to_string().contains("New contributor: maurino"));
+        assert!(tracker.deferred_welcomes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn applies_a_reloaded_interval_to_the_current_pause() {
+        let server = MockServer::start().await;
+        let dir = TempDir::new().unwrap();
+        let mut config = config(&server, &dir);
+        config.monitoring.check_interval_secs = 3600;
+        // Reloads are validated; nothing is posted here
+        config.discord.webhook_url = Secret::new("https://discord.com/api/webhooks/1/token");
+        let source = ConfigSource {
+            path: dir.path().join("config.toml"),
+            database_url: None,
+            profile: None,
+        };
+        fs::write(&source.path, toml::to_string(&config).unwrap()).unwrap();
+
+        let mut tracker = CommitTracker::new(config.clone())
+            .await
+            .unwrap()
+            .with_watcher(ConfigWatcher::new(source.clone()));
+
+        // Shortened while the tracker is about to wait out the long interval
+        config.monitoring.check_interval_secs = 1;
+        fs::write(&source.path, toml::to_string(&config).unwrap()).unwrap();
+
+        let paused = tokio::time::timeout(
+            Duration::from_secs(10),
+            tracker.pause(CommitTracker::next_wake),
+        )
+        .await;
+        assert!(paused.is_ok(), "still waiting for the old interval");
+        assert_eq!(tracker.config.monitoring.check_interval_secs, 1);
+    }
+
+    /// Fails every post mentioning `text`, at most `times` times.
+    async fn reject_posts_of(server: &MockServer, text: &str, times: u64) {
+        Mock::given(method("POST"))
+            .and(path("/webhook"))
+            .and(body_string_contains(text))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(times)
+            .with_priority(1)
+            .mount(server)
+            .await;
+    }
+
+    async fn attempts(tracker: &CommitTracker, commit_id: i32) -> i32 {
+        let records = tracker.database.get_commit_records(&[commit_id]).await;
+        records.unwrap()[0].deliveries[0].attempts
+    }
+
+    /// A tracker that has already delivered commit 1, so later commits are
+    /// all announced rather than only the latest.
+    async fn started_tracker(server: &MockServer, config: Config) -> CommitTracker {
+        accept_posts(server).await;
+        serve_feed(server, vec![commit(1, "alistair", "First")]).await;
+        let mut tracker = CommitTracker::new(config).await.unwrap();
+        tracker.run_once().await.unwrap();
+        server.reset().await;
+        tracker
+    }
+
+    #[tokio::test]
+    async fn retries_a_failed_commit_after_a_newer_one_was_sent() {
+        let server = MockServer::start().await;
+        let dir = TempDir::new().unwrap();
+        let mut tracker = started_tracker(&server, config(&server, &dir)).await;
+
+        reject_posts_of(&server, "Flaky", 1).await;
+        accept_posts(&server).await;
+        serve_feed(
+            &server,
+            vec![
+                commit(3, "maurino", "Works"),
+                commit(2, "alistair", "Flaky"),
+                commit(1, "alistair", "First"),
+            ],
+        )
+        .await;
+
+        // Commit 2 fails, commit 3 is posted anyway
+        assert!(tracker.run_once().await.is_err());
+        assert_eq!(statuses(&tracker, &[2, 3]).await, ["failed", "sent"]);
+
+        // The next poll posts commit 2 even though 3 is newer
+        tracker.run_once().await.unwrap();
+        assert_eq!(statuses(&tracker, &[2, 3]).await, ["sent", "sent"]);
+        assert_eq!(attempts(&tracker, 2).await, 2);
+
+        let flaky_posts = posts(&server)
+            .await
+            .iter()
+            .filter(|post| post.to_string().contains("Flaky"))
+            .count();
+        assert_eq!(flaky_posts, 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_maximum_attempts() {
+        let server = MockServer::start().await;
+        let dir = TempDir::new().unwrap();
+        let mut tracker = started_tracker(&server, config(&server, &dir)).await;
+
+        reject_posts_of(&server, "Broken", u64::MAX).await;
+        accept_posts(&server).await;
+        serve_feed(
+            &server,
+            vec![
+                commit(2, "alistair", "Broken"),
+                commit(1, "alistair", "First"),
+            ],
+        )
+        .await;
+
+        for _ in 0..MAX_DELIVERY_ATTEMPTS {
+            assert!(tracker.run_once().await.is_err());
+        }
+        tracker.run_once().await.unwrap();
+
+        assert_eq!(statuses(&tracker, &[2]).await, ["failed"]);
+        assert_eq!(attempts(&tracker, 2).await, MAX_DELIVERY_ATTEMPTS);
+        assert_eq!(posts(&server).await.len(), MAX_DELIVERY_ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_bursts_as_a_burst() {
+        let server = MockServer::start().await;
+        let dir = TempDir::new().unwrap();
+        let mut config = config(&server, &dir);
+        config.bursts.enabled = true;
+        let mut tracker = started_tracker(&server, config).await;
+
+        reject_posts_of(&server, "Second", 1).await;
+        accept_posts(&server).await;
+        serve_feed(
+            &server,
+            vec![
+                commit(3, "alistair", "Third"),
+                commit(2, "alistair", "Second"),
+                commit(1, "alistair", "First"),
+            ],
+        )
+        .await;
+
+        assert!(tracker.run_once().await.is_err());
+        assert_eq!(statuses(&tracker, &[2, 3]).await, ["failed", "failed"]);
+
+        tracker.run_once().await.unwrap();
+        assert_eq!(statuses(&tracker, &[2, 3]).await, ["sent", "sent"]);
+        assert_eq!(posts(&server).await.len(), 2);
+    }
 }
@@ -0,0 +1,240 @@
+use crate::core::ConfigOrigins;
+use std::error::Error;
+use std::fmt;
+
+/// One thing wrong with the config.
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    /// Dotted key with array indices, e.g. `discord.mentions[0].roles`
+    pub key: String,
+    pub message: String,
+    /// Where the value was set: `file:line:column`, an environment variable
+    /// or a command-line flag. `None` for defaults.
+    pub origin: Option<String>,
+}
+
+/// Every problem found in a config, reported together rather than one at a
+/// time.
+#[derive(Debug)]
+pub struct ConfigErrors {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl ConfigErrors {
+    /// Fills in where each problem's value was set, unless already known.
+    pub fn locate(mut self, origins: &ConfigOrigins) -> Self {
+        for problem in &mut self.problems {
+            if problem.origin.is_none() {
+                problem.origin = origins.origin(&problem.key);
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.problems.len() {
+            1 => write!(f, "Found 1 problem in the config:")?,
+            count => write!(f, "Found {} problems in the config:", count)?,
+        }
+
+        for problem in &self.problems {
+            let mut lines = problem.message.lines();
+            write!(f, "\n  ❌ {}", problem.key)?;
+            if let Some(origin) = &problem.origin {
+                write!(f, " ({})", origin)?;
+            }
+            write!(f, ": {}", lines.next().unwrap_or_default())?;
+            for line in lines {
+                write!(f, "\n     {}", line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ConfigErrors {}
+
+/// Collects problems while a config is checked.
+#[derive(Default)]
+pub(crate) struct Problems(Vec<ConfigProblem>);
+
+impl Problems {
+    pub fn push(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.0.push(ConfigProblem {
+            key: key.into(),
+            message: message.into(),
+            origin: None,
+        });
+    }
+
+    /// A problem with a value from a known source, e.g. an environment
+    /// variable that couldn't be applied.
+    pub fn push_from(
+        &mut self,
+        key: impl Into<String>,
+        origin: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.0.push(ConfigProblem {
+            key: key.into(),
+            message: message.into(),
+            origin: Some(origin.into()),
+        });
+    }
+
+    pub fn check<T>(&mut self, key: impl Into<String>, result: Result<T, String>) {
+        if let Err(message) = result {
+            self.push(key, message);
+        }
+    }
+
+    pub fn finish(self) -> Result<(), ConfigErrors> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors { problems: self.0 })
+        }
+    }
+
+    /// Like `finish`, also reporting the problems another check found.
+    pub fn finish_with<T>(mut self, result: Result<T, ConfigErrors>) -> Result<T, ConfigErrors> {
+        match result {
+            Ok(value) => self.finish().map(|()| value),
+            Err(errors) => {
+                self.0.extend(errors.problems);
+                Err(ConfigErrors { problems: self.0 })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn errors(keys: &[&str]) -> ConfigErrors {
+        let mut problems = Problems::default();
+        for key in keys {
+            problems.push(*key, "Wrong\nSecond line");
+        }
+        problems.finish().unwrap_err()
+    }
+
+    fn origins(dir: &TempDir, content: &str) -> ConfigOrigins {
+        let path = dir.path().join("config.toml");
+        fs::write(&path, content).unwrap();
+        ConfigOrigins::new(&path)
+    }
+
+    #[test]
+    fn finishes_without_problems() {
+        assert!(Problems::default().finish().is_ok());
+
+        let mut problems = Problems::default();
+        problems.check("a", Ok::<_, String>(()));
+        assert!(problems.finish().is_ok());
+    }
+
+    #[test]
+    fn locates_keys_in_the_file() {
+        let dir = TempDir::new().unwrap();
+        let origins = origins(
+            &dir,
+            "[monitoring]\ncheck_interval_secs = 0\n\n\
+             [[discord.mentions]]\nroles = [\"123\", \"admins\"]\n",
+        );
+        let file = dir.path().join("config.toml").display().to_string();
+
+        let errors = errors(&[
+            "monitoring.check_interval_secs",
+            "discord.mentions[0].roles[1]",
+            "digest.timezone",
+        ])
+        .locate(&origins);
+
+        let located: Vec<_> = errors.problems.iter().map(|p| p.origin.clone()).collect();
+        assert_eq!(
+            located,
+            [
+                Some(format!("{}:2:23", file)),
+                Some(format!("{}:5:17", file)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn locates_overridden_keys_at_their_source() {
+        let dir = TempDir::new().unwrap();
+        let mut origins = origins(&dir, "[discord]\nmentions = []\n");
+        origins.set("discord.mentions", "RCT_DISCORD__MENTIONS");
+        origins.set("database.url", "--database");
+
+        let errors = errors(&["discord.mentions[0].roles[1]", "database.url"]).locate(&origins);
+
+        assert_eq!(
+            errors.problems[0].origin.as_deref(),
+            Some("RCT_DISCORD__MENTIONS")
+        );
+        assert_eq!(errors.problems[1].origin.as_deref(), Some("--database"));
+    }
+
+    #[test]
+    fn locates_profile_keys_before_shared_ones() {
+        let dir = TempDir::new().unwrap();
+        let origins = origins(
+            &dir,
+            "[monitoring]\ncheck_interval_secs = 0\n\n\
+             [profiles.beta.monitoring]\ncheck_interval_secs = 0\n",
+        )
+        .for_profile("beta");
+        let file = dir.path().join("config.toml").display().to_string();
+
+        let errors = errors(&["monitoring.check_interval_secs"]).locate(&origins);
+        assert_eq!(errors.problems[0].origin, Some(format!("{}:5:23", file)));
+    }
+
+    #[test]
+    fn locates_environment_overrides_before_profile_keys() {
+        let dir = TempDir::new().unwrap();
+        let mut origins = origins(
+            &dir,
+            "[profiles.beta.monitoring]\ncheck_interval_secs = 0\n",
+        );
+        origins.set(
+            "monitoring.check_interval_secs",
+            "RCT_MONITORING__CHECK_INTERVAL_SECS",
+        );
+        let origins = origins.for_profile("beta");
+
+        let errors = errors(&["monitoring.check_interval_secs"]).locate(&origins);
+        assert_eq!(
+            errors.problems[0].origin.as_deref(),
+            Some("RCT_MONITORING__CHECK_INTERVAL_SECS")
+        );
+    }
+
+    #[test]
+    fn lists_every_problem_with_its_origin() {
+        let mut errors = errors(&["monitoring.check_interval_secs", "digest.timezone"]);
+        errors.problems[0].origin = Some("config.toml:2:23".to_string());
+
+        assert_eq!(
+            errors.to_string(),
+            "Found 2 problems in the config:\n  \
+             ❌ monitoring.check_interval_secs (config.toml:2:23): Wrong\n     Second line\n  \
+             ❌ digest.timezone: Wrong\n     Second line"
+        );
+        errors.problems.truncate(1);
+        assert!(
+            errors
+                .to_string()
+                .starts_with("Found 1 problem in the config:")
+        );
+    }
+}
@@ -1,23 +1,104 @@
+use clap::Parser;
 use log::error;
-use rust_commit_tracker::CommitTracker;
+use rust_commit_tracker::cli::{self, Cli, Command};
+use rust_commit_tracker::core::{ConfigCreated, current_profile, redact};
+use std::error::Error;
+use std::io::Write;
 
 #[tokio::main]
 async fn main() {
-    // Initialize logger
+    let mut cli = Cli::parse();
+
+    // Initialize logger, scrubbing secrets from every line and tagging
+    // lines from profile trackers with the profile name
     env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .format_timestamp_secs()
+        .filter_level(cli.log_level)
+        .format(|buf, record| {
+            let style = buf.default_level_style(record.level());
+            let profile = current_profile()
+                .map(|name| format!("[{}] ", name))
+                .unwrap_or_default();
+            writeln!(
+                buf,
+                "[{} {style}{:<5}{style:#} {}] {}{}",
+                buf.timestamp_seconds(),
+                record.level(),
+                record.target(),
+                profile,
+                redact(&record.args().to_string())
+            )
+        })
         .init();
 
-    let mut tracker = match CommitTracker::new().await {
-        Ok(tracker) => tracker,
-        Err(e) => {
-            error!("❌ Failed to initialize tracker: {}", e);
+    let command = cli.command.take().unwrap_or(Command::Run);
+    if let Command::Init(args) = &command {
+        if let Err(e) = cli::init::run(&cli.config, args).await {
+            error!("❌ Init failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let command = match command {
+        // Config commands load the config themselves, if at all
+        Command::Config(command) => {
+            if let Err(e) = cli::config::run(&cli, command) {
+                error!("❌ Config command failed: {}", e);
+                std::process::exit(exit_code(e.as_ref()));
+            }
             return;
         }
+        command => command,
+    };
+
+    // Status only looks, so it never creates or upgrades the file
+    let loaded = match command {
+        Command::Status => cli.source().read(),
+        _ => cli.load_config(),
+    };
+    let config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            error!("❌ Failed to load config: {}", e);
+            std::process::exit(exit_code(e.as_ref()));
+        }
+    };
+    if !config.profiles.is_empty() && !matches!(command, Command::Run) {
+        error!(
+            "❌ The config declares profiles ({}); choose one with --profile",
+            config.profile_names().join(", ")
+        );
+        std::process::exit(1);
+    }
+
+    let (action, result) = match command {
+        Command::Init(_) | Command::Config(_) => {
+            unreachable!("init and config run before the config is loaded")
+        }
+        Command::Run => ("Tracker", cli::run::run(config, cli.source()).await),
+        Command::Once => ("Poll", cli::run::once(config).await),
+        Command::Backfill(args) => ("Backfill", cli::backfill::run(config, args).await),
+        Command::Status => ("Status", cli::status::run(config).await),
+        Command::Replay(args) => ("Replay", cli::replay::run(config, args).await),
+        Command::TestWebhook => ("Webhook test", cli::webhook::run(config).await),
+        Command::Search(args) => ("Search", cli::search::run(config, args).await),
+        Command::Export(args) => ("Export", cli::export::run(config, args).await),
+        Command::Import(args) => ("Import", cli::import::run(config, args).await),
+        Command::Backup(args) => ("Backup", cli::backup::backup(config, args).await),
+        Command::Restore(args) => ("Restore", cli::backup::restore(config, args).await),
+        Command::Gaps(args) => ("Gap check", cli::gaps::run(config, args).await),
+        Command::Authors(args) => ("Author lookup", cli::authors::run(config, args).await),
     };
 
-    if let Err(e) = tracker.start().await {
-        error!("❌ Fatal error: {}", e);
+    if let Err(e) = result {
+        error!("❌ {} failed: {}", action, e);
+        std::process::exit(1);
+    }
+}
+
+fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if error.is::<ConfigCreated>() {
+        ConfigCreated::EXIT_CODE
+    } else {
+        1
     }
 }
@@ -0,0 +1,35 @@
+/// Someone who has committed, as tracked in the author registry.
+#[derive(Debug, Clone)]
+pub struct Author {
+    pub name: String,
+    /// Most recent avatar
+    pub avatar: String,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+    pub first_commit_id: i32,
+    pub last_commit_id: i32,
+    pub commit_count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthorRepo {
+    pub repo: String,
+    pub commit_count: i64,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthorAvatar {
+    pub avatar: String,
+    pub first_seen_at: String,
+}
+
+/// An author with their per-repository activity and avatar history.
+#[derive(Debug, Clone)]
+pub struct AuthorProfile {
+    pub author: Author,
+    pub repos: Vec<AuthorRepo>,
+    /// Oldest first
+    pub avatars: Vec<AuthorAvatar>,
+}
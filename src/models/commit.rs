@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct CommitsResponse {
@@ -8,7 +9,7 @@ pub struct CommitsResponse {
     pub results: Vec<CommitInfo>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitInfo {
     pub id: i32,
     pub repo: String,
@@ -19,17 +20,45 @@ pub struct CommitInfo {
     pub dislikes: u32,
     pub message: String,
     pub user: CommitUser,
+    /// Fields we don't model, kept so the stored raw JSON is complete
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitUser {
     pub name: String,
     pub avatar: String,
 }
 
+/// One branch a change landed on, used when the same commit is posted once
+/// for several branches.
+#[derive(Debug, Clone)]
+pub struct BranchCommit {
+    pub commit_id: i32,
+    pub branch: String,
+    pub changeset: String,
+}
+
+impl BranchCommit {
+    pub fn link(&self, commits_url: &str) -> String {
+        commit_link(commits_url, self.commit_id)
+    }
+}
+
+impl From<&CommitInfo> for BranchCommit {
+    fn from(commit: &CommitInfo) -> Self {
+        Self {
+            commit_id: commit.id,
+            branch: commit.branch.clone(),
+            changeset: commit.changeset.clone(),
+        }
+    }
+}
+
 impl CommitInfo {
-    pub fn link(&self) -> String {
-        format!("https://commits.facepunch.com/{}", self.id)
+    pub fn link(&self, commits_url: &str) -> String {
+        commit_link(commits_url, self.id)
     }
 
     pub fn avatar_url(&self) -> &str {
@@ -39,4 +68,113 @@ impl CommitInfo {
     pub fn author(&self) -> &str {
         &self.user.name
     }
+
+    /// When the commit was made, if the feed gave a readable time.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        parse_commit_time(&self.created)
+    }
+
+    /// Whether `other` was made within `window_secs` of this commit. Commits
+    /// without a readable time are never considered close.
+    pub fn is_within(&self, other: &CommitInfo, window_secs: u64) -> bool {
+        match (self.created_at(), other.created_at()) {
+            (Some(a), Some(b)) => (a - b).abs() <= chrono::Duration::seconds(window_secs as i64),
+            _ => false,
+        }
+    }
+
+    /// Message normalized for duplicate detection: lowercased, whitespace
+    /// collapsed and trailing punctuation removed.
+    pub fn message_key(&self) -> String {
+        self.message
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end_matches(['.', '!', ';', ','])
+            .to_lowercase()
+    }
+}
+
+/// Parses a commit time from the feed. The feed gives UTC times without an
+/// offset, e.g. `2025-01-01T12:34:56.78`; RFC 3339 times are accepted too.
+/// Page of commit `commit_id` on the site serving the feed at
+/// `commits_url`, e.g. `https://commits.facepunch.com/123`.
+pub fn commit_link(commits_url: &str, commit_id: i32) -> String {
+    reqwest::Url::parse(commits_url)
+        .and_then(|url| url.join(&format!("/{}", commit_id)))
+        .map(String::from)
+        .unwrap_or_else(|_| format!("https://commits.facepunch.com/{}", commit_id))
+}
+
+pub fn parse_commit_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(created: &str) -> CommitInfo {
+        CommitInfo {
+            id: 1,
+            repo: "rust_reboot".to_string(),
+            branch: "main".to_string(),
+            changeset: "abc".to_string(),
+            created: created.to_string(),
+            likes: 0,
+            dislikes: 0,
+            message: "Fixed a thing".to_string(),
+            user: CommitUser {
+                name: "Alistair".to_string(),
+                avatar: String::new(),
+            },
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    #[test]
+    fn parses_feed_and_rfc3339_times() {
+        let expected: DateTime<Utc> = "2025-01-01T12:34:56.780Z".parse().unwrap();
+        assert_eq!(parse_commit_time("2025-01-01T12:34:56.78"), Some(expected));
+        assert_eq!(
+            parse_commit_time("2025-01-01T13:34:56.78+01:00"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_commit_time("2025-01-01T12:34:56"),
+            Some("2025-01-01T12:34:56Z".parse().unwrap())
+        );
+        assert_eq!(parse_commit_time(""), None);
+        assert_eq!(parse_commit_time("yesterday"), None);
+    }
+
+    #[test]
+    fn compares_commit_times_against_a_window() {
+        let first = commit("2025-01-01T12:00:00");
+        assert!(first.is_within(&commit("2025-01-01T12:59:59"), 3600));
+        assert!(first.is_within(&commit("2025-01-01T11:00:00"), 3600));
+        assert!(!first.is_within(&commit("2025-01-01T15:00:00"), 3600));
+        assert!(!first.is_within(&commit(""), 3600));
+    }
+
+    #[test]
+    fn links_commits_on_the_site_serving_the_feed() {
+        assert_eq!(
+            commit_link("https://commits.facepunch.com/?format=json", 42),
+            "https://commits.facepunch.com/42"
+        );
+        assert_eq!(
+            commit_link("http://127.0.0.1:8080/mirror/feed.json", 42),
+            "http://127.0.0.1:8080/42"
+        );
+        assert_eq!(
+            commit("").link("not a url"),
+            "https://commits.facepunch.com/1"
+        );
+    }
 }
@@ -0,0 +1,61 @@
+use crate::models::CommitInfo;
+use serde::{Deserialize, Serialize};
+
+/// Where a commit stands for one destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Held back in a burst until its `deliver_at`
+    Pending,
+    /// Reserved by an instance that is about to post it
+    Claimed,
+    /// Posted successfully
+    Sent,
+    /// Held back during quiet hours, to be posted in a summary
+    Deferred,
+    /// Deliberately not posted, e.g. a duplicate of an earlier post
+    Skipped,
+    /// The last attempt failed and will be retried
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Claimed => "claimed",
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Deferred => "deferred",
+            DeliveryStatus::Skipped => "skipped",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A stored commit with its delivery state, as written to archives and
+/// exports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitRecord {
+    pub commit: CommitInfo,
+    pub first_seen_at: String,
+    pub deliveries: Vec<DeliveryRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    pub destination: String,
+    pub status: String,
+    pub attempts: i32,
+    pub message_id: Option<String>,
+    pub response_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub delivered_at: Option<String>,
+}
+
+/// What an import added; records already present are left untouched.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportSummary {
+    pub commits: u64,
+    pub deliveries: u64,
+}
@@ -0,0 +1,63 @@
+use crate::models::commit_link;
+use chrono::{DateTime, Utc};
+
+/// Which period a digest summarizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestPeriod {
+    Daily,
+    Weekly,
+}
+
+impl DigestPeriod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DigestPeriod::Daily => "Daily",
+            DigestPeriod::Weekly => "Weekly",
+        }
+    }
+
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            DigestPeriod::Daily => chrono::Duration::days(1),
+            DigestPeriod::Weekly => chrono::Duration::weeks(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchActivity {
+    pub repo: String,
+    pub branch: String,
+    pub commits: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthorActivity {
+    pub author: String,
+    pub commits: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestCommit {
+    pub commit_id: i32,
+    pub author: String,
+    pub message: String,
+    pub likes: i64,
+}
+
+impl DigestCommit {
+    pub fn link(&self, commits_url: &str) -> String {
+        commit_link(commits_url, self.commit_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestReport {
+    pub period: DigestPeriod,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub total_commits: i64,
+    pub branches: Vec<BranchActivity>,
+    pub top_authors: Vec<AuthorActivity>,
+    pub top_commits: Vec<DigestCommit>,
+}
@@ -1,8 +1,19 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
 pub struct DiscordEmbed {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
     pub embeds: Vec<EmbedData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentions>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllowedMentions {
+    pub parse: Vec<String>,
+    pub roles: Vec<String>,
+    pub users: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -35,3 +46,9 @@ pub struct EmbedFooter {
     pub text: String,
     pub icon_url: String,
 }
+
+/// Message returned by Discord when a webhook is executed with `wait=true`.
+#[derive(Debug, Deserialize)]
+pub struct WebhookMessage {
+    pub id: String,
+}
@@ -0,0 +1,17 @@
+/// A run of consecutive commit IDs missing from the database, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitGap {
+    pub first: i32,
+    pub last: i32,
+}
+
+impl CommitGap {
+    /// Number of missing IDs.
+    pub fn size(&self) -> i64 {
+        i64::from(self.last) - i64::from(self.first) + 1
+    }
+
+    pub fn contains(&self, commit_id: i32) -> bool {
+        (self.first..=self.last).contains(&commit_id)
+    }
+}
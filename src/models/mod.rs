@@ -1,5 +1,15 @@
+pub mod author;
 pub mod commit;
+pub mod delivery;
+pub mod digest;
 pub mod discord;
+pub mod gap;
+pub mod search;
 
+pub use author::*;
 pub use commit::*;
+pub use delivery::*;
+pub use digest::*;
 pub use discord::*;
+pub use gap::*;
+pub use search::*;
@@ -0,0 +1,42 @@
+use crate::models::commit_link;
+use chrono::NaiveDate;
+
+/// Narrows stored commits down for searches and exports. Dates filter on
+/// the day the commit was made and are inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct CommitFilter {
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    pub author: Option<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+/// Full-text search over stored commits.
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub text: String,
+    pub filter: CommitFilter,
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub commit_id: i32,
+    pub repo: String,
+    pub branch: String,
+    pub author: String,
+    pub message: String,
+    /// When the commit was made, as reported by the feed
+    pub created: String,
+    /// Excerpt of the message with matches wrapped in `**`
+    pub snippet: String,
+    /// Relevance; higher is better
+    pub rank: f64,
+}
+
+impl SearchHit {
+    pub fn link(&self, commits_url: &str) -> String {
+        commit_link(commits_url, self.commit_id)
+    }
+}
@@ -0,0 +1,85 @@
+use crate::models::CommitRecord;
+use crate::services::export::{self, ExportFormat};
+use chrono::Utc;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Writes commits to a new gzipped NDJSON file in `dir`, one record per
+/// line. The file only appears under its final name once fully written, so
+/// a failed archive never looks complete.
+pub fn write_archive(dir: &Path, records: &[CommitRecord]) -> Result<PathBuf, Box<dyn Error>> {
+    let (first, last) = match (records.first(), records.last()) {
+        (Some(first), Some(last)) => (first.commit.id, last.commit.id),
+        _ => return Err("Nothing to archive".into()),
+    };
+
+    fs::create_dir_all(dir)?;
+    let name = format!(
+        "commits-{}-{}-{}.ndjson.gz",
+        first,
+        last,
+        Utc::now().format("%Y%m%d%H%M%S")
+    );
+    let path = dir.join(&name);
+    let partial = dir.join(format!("{}.partial", name));
+
+    let file = File::create(&partial)?;
+    let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    export::write_records(&mut encoder, ExportFormat::Ndjson, records)?;
+    let file = encoder.finish()?.into_inner()?;
+    file.sync_all()?;
+
+    fs::rename(&partial, &path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+    use std::io::BufReader;
+    use tempfile::TempDir;
+
+    fn record(id: i32) -> CommitRecord {
+        serde_json::from_value(json!({
+            "commit": {
+                "id": id,
+                "repo": "rust_reboot",
+                "branch": "main",
+                "changeset": format!("c{}", id),
+                "created": "2025-01-01T12:00:00",
+                "likes": 0,
+                "dislikes": 0,
+                "message": "Change",
+                "user": { "name": "alice", "avatar": "" },
+            },
+            "first_seen_at": "2025-01-01 12:00:00",
+            "deliveries": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn writes_records_as_gzipped_ndjson() {
+        let dir = TempDir::new().unwrap();
+        let archive_dir = dir.path().join("archive");
+
+        let path = write_archive(&archive_dir, &[record(3), record(7)]).unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("commits-3-7-"), "{}", name);
+        assert!(name.ends_with(".ndjson.gz"), "{}", name);
+        assert_eq!(fs::read_dir(&archive_dir).unwrap().count(), 1);
+
+        let reader = BufReader::new(GzDecoder::new(File::open(&path).unwrap()));
+        let records = export::read_records(reader, ExportFormat::Ndjson).unwrap();
+        let ids: Vec<i32> = records.iter().map(|r| r.commit.id).collect();
+        assert_eq!(ids, vec![3, 7]);
+
+        assert!(write_archive(&archive_dir, &[]).is_err());
+    }
+}
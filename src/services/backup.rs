@@ -0,0 +1,282 @@
+use crate::services::database::{Backend, sqlite_file};
+use crate::services::{Database, migrations};
+use chrono::Utc;
+use log::{debug, info};
+use sqlx::Row;
+use sqlx::any::AnyPoolOptions;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snapshots the SQLite database into `dir` as `<name>-<timestamp>.db`, then
+/// removes all but the newest `keep` snapshots (0 keeps all of them).
+pub async fn create_backup(
+    database: &Database,
+    database_url: &str,
+    dir: &Path,
+    keep: usize,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let prefix = backup_prefix(database_url)?;
+    fs::create_dir_all(dir)?;
+
+    let path = dir.join(format!(
+        "{}-{}.db",
+        prefix,
+        Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    if path.exists() {
+        return Err(format!("Backup '{}' already exists", path.display()).into());
+    }
+    database.backup_into(&path).await?;
+
+    if keep > 0 {
+        for old in list_backups(dir, &prefix)?.into_iter().rev().skip(keep) {
+            fs::remove_file(&old)?;
+            debug!("Removed old backup {}", old.display());
+        }
+    }
+
+    Ok(path)
+}
+
+/// Replaces the SQLite database with a backup after checking the backup is
+/// intact and not from a newer version. The current database is kept next to
+/// it as `<file>.before-restore`. Returns the backup's schema version.
+pub async fn restore_backup(database_url: &str, backup: &Path) -> Result<i64, Box<dyn Error>> {
+    let target = sqlite_file(database_url)
+        .ok_or("Restoring is only supported for SQLite; use pg_restore for PostgreSQL")?;
+    if !backup.is_file() {
+        return Err(format!("Backup '{}' not found", backup.display()).into());
+    }
+
+    let version = inspect_backup(backup).await?;
+    let latest = migrations::latest_version(Backend::Sqlite);
+    if version > latest {
+        return Err(format!(
+            "Backup schema version {} is newer than this binary supports ({}). \
+            Please upgrade rust-commit-tracker first.",
+            version, latest
+        )
+        .into());
+    }
+
+    if target.exists() {
+        let previous = with_suffix(target, ".before-restore");
+        fs::copy(target, &previous)?;
+        info!("💾 Saved current database to {}", previous.display());
+    }
+
+    // Copy next to the target first so the swap itself is a single rename
+    let staging = with_suffix(target, ".restoring");
+    fs::copy(backup, &staging)?;
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let leftover = with_suffix(target, suffix);
+        if leftover.exists() {
+            fs::remove_file(leftover)?;
+        }
+    }
+    fs::rename(&staging, target)?;
+
+    // Bring an older backup up to the current schema
+    Database::new(database_url).await?;
+
+    Ok(version)
+}
+
+/// Opens a backup read-only and returns its schema version. Databases from
+/// before versioned migrations report 0.
+async fn inspect_backup(backup: &Path) -> Result<i64, Box<dyn Error>> {
+    sqlx::any::install_default_drivers();
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect(&format!("sqlite:{}?mode=ro", backup.display()))
+        .await
+        .map_err(|e| format!("Can't open backup '{}': {}", backup.display(), e))?;
+
+    let row = sqlx::query("PRAGMA integrity_check")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| format!("Can't read backup '{}': {}", backup.display(), e))?;
+    let integrity: String = row.get(0);
+    if integrity != "ok" {
+        return Err(format!("Backup failed the integrity check: {}", integrity).into());
+    }
+
+    let row = sqlx::query(
+        "SELECT \
+            (SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_version') \
+                as versioned, \
+            (SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'sent_commits') \
+                as legacy",
+    )
+    .fetch_one(&pool)
+    .await?;
+    let versioned: i64 = row.get("versioned");
+    let legacy: i64 = row.get("legacy");
+
+    let version = if versioned > 0 {
+        migrations::current_version(&pool).await?
+    } else if legacy > 0 {
+        0
+    } else {
+        return Err(format!(
+            "'{}' is not a rust-commit-tracker database",
+            backup.display()
+        )
+        .into());
+    };
+
+    pool.close().await;
+    Ok(version)
+}
+
+/// Backups in `dir` made for the database named `prefix`, oldest first.
+fn list_backups(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_backup = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(prefix)?.strip_prefix('-'))
+            .and_then(|rest| rest.strip_suffix(".db"))
+            .is_some_and(|stamp| stamp.len() == 15 && stamp.as_bytes()[8] == b'-');
+        if is_backup {
+            backups.push(path);
+        }
+    }
+
+    // Timestamps sort chronologically as text
+    backups.sort();
+    Ok(backups)
+}
+
+fn backup_prefix(database_url: &str) -> Result<String, Box<dyn Error>> {
+    let file_path = sqlite_file(database_url)
+        .ok_or("Backups are only supported for SQLite; use pg_dump for PostgreSQL")?;
+    let stem = file_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("commits");
+    Ok(stem.to_string())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn commit(id: i32) -> crate::models::CommitInfo {
+        serde_json::from_value(json!({
+            "id": id,
+            "repo": "rust_reboot",
+            "branch": "main",
+            "changeset": format!("c{}", id),
+            "created": "2025-01-01T12:00:00",
+            "likes": 0,
+            "dislikes": 0,
+            "message": "Change",
+            "user": { "name": "alice", "avatar": "" },
+        }))
+        .unwrap()
+    }
+
+    fn url(dir: &TempDir) -> String {
+        format!("sqlite:{}", dir.path().join("commits.db").display())
+    }
+
+    #[tokio::test]
+    async fn restores_a_backup_over_the_current_database() {
+        let dir = TempDir::new().unwrap();
+        let url = url(&dir);
+        let backups = dir.path().join("backups");
+        let database = Database::new(&url).await.unwrap();
+        database.record_commit(&commit(1)).await.unwrap();
+
+        let backup = create_backup(&database, &url, &backups, 0).await.unwrap();
+        assert!(backup.starts_with(&backups));
+        database.record_commit(&commit(2)).await.unwrap();
+        drop(database);
+
+        let version = restore_backup(&url, &backup).await.unwrap();
+        assert_eq!(version, migrations::latest_version(Backend::Sqlite));
+        assert!(dir.path().join("commits.db.before-restore").is_file());
+
+        let database = Database::new(&url).await.unwrap();
+        assert_eq!(database.get_sent_commits_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_only_the_newest_backups() {
+        let dir = TempDir::new().unwrap();
+        let url = url(&dir);
+        let backups = dir.path().join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        for name in [
+            "commits-20240101-000000.db",
+            "commits-20240102-000000.db",
+            "other-20240101-000000.db",
+            "commits-notes.db",
+        ] {
+            fs::write(backups.join(name), "").unwrap();
+        }
+        let database = Database::new(&url).await.unwrap();
+
+        let backup = create_backup(&database, &url, &backups, 2).await.unwrap();
+        let mut names: Vec<String> = fs::read_dir(&backups)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let newest = backup.file_name().unwrap().to_str().unwrap().to_string();
+        assert_eq!(
+            names,
+            vec![
+                "commits-20240102-000000.db".to_string(),
+                newest,
+                "commits-notes.db".to_string(),
+                "other-20240101-000000.db".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_backups_it_cannot_use() {
+        let dir = TempDir::new().unwrap();
+        let url = url(&dir);
+        let database = Database::new(&url).await.unwrap();
+        let backup = dir.path().join("newer.db");
+        database.backup_into(&backup).await.unwrap();
+        drop(database);
+
+        let pool = AnyPoolOptions::new()
+            .connect(&format!("sqlite:{}", backup.display()))
+            .await
+            .unwrap();
+        let newer = migrations::latest_version(Backend::Sqlite) + 1;
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, 'future')")
+            .bind(newer)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let error = restore_backup(&url, &backup).await.unwrap_err().to_string();
+        assert!(error.contains("newer than this binary"), "{}", error);
+
+        let missing = dir.path().join("missing.db");
+        assert!(restore_backup(&url, &missing).await.is_err());
+        let postgres = restore_backup("postgres://localhost/tracker", &backup).await;
+        assert!(postgres.unwrap_err().to_string().contains("pg_restore"));
+
+        // The current database is left alone
+        assert!(!dir.path().join("commits.db.before-restore").exists());
+    }
+}
@@ -1,101 +1,965 @@
-use log::{debug, info};
-use sqlx::{Row, SqlitePool};
+use crate::core::{RetentionConfig, redact};
+use crate::models::{
+    Author, AuthorActivity, AuthorAvatar, AuthorProfile, AuthorRepo, BranchActivity, BranchCommit,
+    CommitFilter, CommitGap, CommitInfo, CommitRecord, DeliveryRecord, DeliveryStatus,
+    DigestCommit, DigestPeriod, DigestReport, ImportSummary, SearchHit, SearchQuery,
+    parse_commit_time,
+};
+use crate::services::migrations;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use log::debug;
+use sqlx::any::{AnyPoolOptions, AnyRow};
+use sqlx::{AnyConnection, AnyPool, Row};
+use std::collections::BTreeSet;
 use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+
+/// Timestamps are exchanged with the database as UTC text in the format of
+/// SQLite's `CURRENT_TIMESTAMP`. Queries pass them through `datetime()` both
+/// ways: SQLite keeps them as text that compares correctly as strings, and
+/// PostgreSQL, whose migrations define a matching `datetime()`, stores them
+/// as `TIMESTAMPTZ`.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Delivery statuses that mean a commit has been handled, or is queued to be,
+/// and must not be picked up again as new. Failed deliveries are retried
+/// separately.
+const HANDLED_STATUSES: &str = "('sent', 'pending', 'deferred', 'skipped', 'failed')";
+
+/// How many commit IDs go into one `IN (...)` list, well below the bind
+/// parameter limits of both engines.
+const ID_CHUNK_SIZE: usize = 500;
+
+/// How many earlier posts with the same author and message are checked for
+/// a duplicate; older ones are far outside any sensible window.
+const DUPLICATE_CANDIDATES: i64 = 20;
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+fn author_from_row(row: &AnyRow) -> Author {
+    Author {
+        name: row.get("name"),
+        avatar: row.get("avatar"),
+        first_seen_at: row.get("first_seen_at"),
+        last_seen_at: row.get("last_seen_at"),
+        first_commit_id: row.get("first_commit_id"),
+        last_commit_id: row.get("last_commit_id"),
+        commit_count: row.get("commit_count"),
+    }
+}
+
+/// Inserts a commit unless it's already stored; returns whether it was new.
+async fn insert_commit(
+    conn: &mut AnyConnection,
+    commit: &CommitInfo,
+    first_seen_at: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO commits (
+            commit_id, repo, branch, changeset, created, likes, dislikes, message,
+            message_key, author, avatar, raw_json, first_seen_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, datetime($13))
+        ON CONFLICT (commit_id) DO NOTHING
+        "#,
+    )
+    .bind(commit.id)
+    .bind(&commit.repo)
+    .bind(&commit.branch)
+    .bind(&commit.changeset)
+    .bind(&commit.created)
+    .bind(commit.likes as i64)
+    .bind(commit.dislikes as i64)
+    .bind(&commit.message)
+    .bind(commit.message_key())
+    .bind(commit.author())
+    .bind(commit.avatar_url())
+    .bind(serde_json::to_string(commit)?)
+    .bind(first_seen_at)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Counts a newly stored commit towards its author, repository and avatar.
+/// Returns true if the author wasn't known yet. Commits may arrive out of
+/// order, so the latest avatar follows the highest commit ID.
+async fn register_author(
+    conn: &mut AnyConnection,
+    commit: &CommitInfo,
+    seen_at: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO authors (
+            name, avatar, first_seen_at, last_seen_at, first_commit_id, last_commit_id,
+            commit_count
+        )
+        VALUES ($1, $2, datetime($3), datetime($3), $4, $4, 1)
+        ON CONFLICT (name) DO NOTHING
+        "#,
+    )
+    .bind(commit.author())
+    .bind(commit.avatar_url())
+    .bind(seen_at)
+    .bind(commit.id)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if !inserted {
+        sqlx::query(
+            r#"
+            UPDATE authors SET
+                avatar = CASE WHEN $2 > last_commit_id THEN $3 ELSE avatar END,
+                first_seen_at = CASE
+                    WHEN datetime($4) < first_seen_at THEN datetime($4) ELSE first_seen_at
+                END,
+                last_seen_at = CASE
+                    WHEN datetime($4) > last_seen_at THEN datetime($4) ELSE last_seen_at
+                END,
+                first_commit_id = CASE
+                    WHEN $2 < first_commit_id THEN $2 ELSE first_commit_id
+                END,
+                last_commit_id = CASE WHEN $2 > last_commit_id THEN $2 ELSE last_commit_id END,
+                commit_count = commit_count + 1
+            WHERE name = $1
+            "#,
+        )
+        .bind(commit.author())
+        .bind(commit.id)
+        .bind(commit.avatar_url())
+        .bind(seen_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO author_repos (author, repo, commit_count, first_seen_at, last_seen_at)
+        VALUES ($1, $2, 1, datetime($3), datetime($3))
+        ON CONFLICT (author, repo) DO UPDATE SET
+            commit_count = author_repos.commit_count + 1,
+            first_seen_at = CASE
+                WHEN excluded.first_seen_at < author_repos.first_seen_at
+                THEN excluded.first_seen_at ELSE author_repos.first_seen_at
+            END,
+            last_seen_at = CASE
+                WHEN excluded.last_seen_at > author_repos.last_seen_at
+                THEN excluded.last_seen_at ELSE author_repos.last_seen_at
+            END
+        "#,
+    )
+    .bind(commit.author())
+    .bind(&commit.repo)
+    .bind(seen_at)
+    .execute(&mut *conn)
+    .await?;
+
+    if !commit.avatar_url().is_empty() {
+        sqlx::query(
+            r#"
+            INSERT INTO author_avatars (author, avatar, first_seen_at)
+            VALUES ($1, $2, datetime($3))
+            ON CONFLICT (author, avatar) DO UPDATE SET
+                first_seen_at = CASE
+                    WHEN excluded.first_seen_at < author_avatars.first_seen_at
+                    THEN excluded.first_seen_at ELSE author_avatars.first_seen_at
+                END
+            "#,
+        )
+        .bind(commit.author())
+        .bind(commit.avatar_url())
+        .bind(seen_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(inserted)
+}
+
+/// `$1, $2, ...` for an `IN (...)` list of `count` values.
+fn placeholders(count: usize) -> String {
+    (1..=count)
+        .map(|n| format!("${}", n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Storage engine behind a database URL. Queries are written in the SQL
+/// both engines understand; the backend only matters for migrations and
+/// engine-specific features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Sqlite => "SQLite",
+            Backend::Postgres => "PostgreSQL",
+        }
+    }
+
+    pub fn from_url(database_url: &str) -> Result<Self, Box<dyn Error>> {
+        let scheme = database_url.split(':').next().unwrap_or_default();
+
+        match scheme {
+            "sqlite" => Ok(Backend::Sqlite),
+            "postgres" | "postgresql" if cfg!(feature = "postgres") => Ok(Backend::Postgres),
+            "postgres" | "postgresql" => Err(
+                "PostgreSQL support is not compiled in. Rebuild with `--features postgres`".into(),
+            ),
+            // Only the scheme; the rest of the URL may hold a password
+            _ => Err(format!(
+                "Unsupported database URL scheme '{}'. Expected sqlite: or postgres://",
+                scheme
+            )
+            .into()),
+        }
+    }
+}
+
+/// Path of the database file behind a `sqlite:` URL.
+pub fn sqlite_file(database_url: &str) -> Option<&Path> {
+    let file_path = database_url.strip_prefix("sqlite:")?;
+    let file_path = file_path.split('?').next().unwrap_or(file_path);
+    Some(Path::new(file_path))
+}
 
 pub struct Database {
-    pool: SqlitePool,
+    pool: AnyPool,
+    backend: Backend,
 }
 
 impl Database {
     pub async fn new(database_url: &str) -> Result<Self, Box<dyn Error>> {
+        let backend = Backend::from_url(database_url)?;
+
         // Create the database file if it doesn't exist (for SQLite)
-        if database_url.starts_with("sqlite:") {
-            let file_path = database_url.strip_prefix("sqlite:").unwrap_or(database_url);
-            if !std::path::Path::new(file_path).exists() {
-                debug!("Creating SQLite database file: {}", file_path);
-                std::fs::File::create(file_path)?;
-            }
+        if let Some(file_path) = sqlite_file(database_url)
+            && !file_path.exists()
+        {
+            debug!("Creating SQLite database file: {}", file_path.display());
+            std::fs::File::create(file_path)?;
         }
 
-        let pool = SqlitePool::connect(database_url).await?;
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new().connect(database_url).await?;
 
-        let db = Self { pool };
+        let db = Self { pool, backend };
         db.initialize().await?;
 
         Ok(db)
     }
 
     async fn initialize(&self) -> Result<(), Box<dyn Error>> {
-        // Create the sent_commits table if it doesn't exist
+        migrations::run(&self.pool, self.backend).await?;
+
+        debug!("Database initialized successfully");
+        Ok(())
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Writes a consistent snapshot of the database to `path`, which must not
+    /// exist yet. Safe to run while the tracker is writing.
+    pub async fn backup_into(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if self.backend != Backend::Sqlite {
+            return Err("Backups are only supported for SQLite; use pg_dump for PostgreSQL".into());
+        }
+
+        sqlx::query("VACUUM INTO $1")
+            .bind(path.to_string_lossy().into_owned())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn schema_version(&self) -> Result<i64, Box<dyn Error>> {
+        migrations::current_version(&self.pool).await
+    }
+
+    /// Stores the full commit, refreshing its reactions if already known. New
+    /// commits are also counted in the author registry; returns true if the
+    /// author has never been seen before.
+    pub async fn record_commit(&self, commit: &CommitInfo) -> Result<bool, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+        let now = timestamp(Utc::now());
+
+        let new_author = if insert_commit(&mut tx, commit, &now).await? {
+            register_author(&mut tx, commit, &now).await?
+        } else {
+            sqlx::query(
+                "UPDATE commits SET likes = $1, dislikes = $2, raw_json = $3 WHERE commit_id = $4",
+            )
+            .bind(commit.likes as i64)
+            .bind(commit.dislikes as i64)
+            .bind(serde_json::to_string(commit)?)
+            .bind(commit.id)
+            .execute(&mut *tx)
+            .await?;
+            false
+        };
+
+        tx.commit().await?;
+        Ok(new_author)
+    }
+
+    /// Whether the commit has been handled for `destination`: posted, held
+    /// in a burst, deferred to a summary, deliberately skipped, or attempted
+    /// and waiting to be retried.
+    pub async fn is_commit_sent(
+        &self,
+        commit_id: i32,
+        destination: &str,
+    ) -> Result<bool, Box<dyn Error>> {
+        let row = sqlx::query(&format!(
+            "SELECT COUNT(*) as count FROM deliveries \
+             WHERE commit_id = $1 AND destination = $2 AND status IN {}",
+            HANDLED_STATUSES
+        ))
+        .bind(commit_id)
+        .bind(destination)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let count: i64 = row.get("count");
+        Ok(count > 0)
+    }
+
+    /// Records a successful post. `message_id` is the Discord message the
+    /// commit appears in, so later duplicates can edit that post.
+    pub async fn mark_commit_sent(
+        &self,
+        commit_id: i32,
+        destination: &str,
+        message_id: Option<&str>,
+        response_code: Option<u16>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.update_delivery(
+            commit_id,
+            destination,
+            DeliveryStatus::Sent,
+            message_id,
+            response_code,
+            None,
+        )
+        .await?;
+
+        debug!("Marked commit {} as sent to {}", commit_id, destination);
+        Ok(())
+    }
+
+    /// Records a commit that was intentionally not posted, pointing at the
+    /// post that already covers it.
+    pub async fn mark_commit_skipped(
+        &self,
+        commit_id: i32,
+        destination: &str,
+        message_id: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.update_delivery(
+            commit_id,
+            destination,
+            DeliveryStatus::Skipped,
+            message_id,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Records a failed post. Secrets are scrubbed from the error, since it
+    /// is kept and exported.
+    pub async fn record_delivery_failure(
+        &self,
+        commit_id: i32,
+        destination: &str,
+        response_code: Option<u16>,
+        error: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.update_delivery(
+            commit_id,
+            destination,
+            DeliveryStatus::Failed,
+            None,
+            response_code,
+            Some(&redact(error)),
+        )
+        .await
+    }
+
+    pub async fn defer_commit(
+        &self,
+        commit_id: i32,
+        destination: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.update_delivery(
+            commit_id,
+            destination,
+            DeliveryStatus::Deferred,
+            None,
+            None,
+            None,
+        )
+        .await?;
+
+        debug!("Deferred commit {} until quiet hours end", commit_id);
+        Ok(())
+    }
+
+    /// Holds a commit back in a burst until `deliver_at`. Recorded so the
+    /// burst survives a restart or moves to the instance taking over.
+    pub async fn mark_commit_pending(
+        &self,
+        commit_id: i32,
+        destination: &str,
+        deliver_at: DateTime<Utc>,
+    ) -> Result<(), Box<dyn Error>> {
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS sent_commits (
-                id INTEGER PRIMARY KEY,
-                commit_id INTEGER NOT NULL UNIQUE,
-                author TEXT NOT NULL,
-                message TEXT NOT NULL,
-                branch TEXT NOT NULL,
-                changeset TEXT NOT NULL,
-                sent_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            INSERT INTO deliveries (
+                commit_id, destination, status, attempts, created_at, updated_at, deliver_at
             )
+            VALUES ($1, $2, 'pending', 0, datetime($3), datetime($3), datetime($4))
+            ON CONFLICT (commit_id, destination) DO UPDATE SET
+                status = excluded.status,
+                updated_at = excluded.updated_at,
+                deliver_at = excluded.deliver_at
+            WHERE deliveries.status IN ('pending', 'failed')
             "#,
         )
+        .bind(commit_id)
+        .bind(destination)
+        .bind(timestamp(Utc::now()))
+        .bind(timestamp(deliver_at))
         .execute(&self.pool)
         .await?;
 
-        debug!("Database initialized successfully");
+        debug!("Holding commit {} in a burst", commit_id);
         Ok(())
     }
 
-    pub async fn is_commit_sent(&self, commit_id: i32) -> Result<bool, Box<dyn Error>> {
-        let row = sqlx::query("SELECT COUNT(*) as count FROM sent_commits WHERE commit_id = ?")
-            .bind(commit_id)
-            .fetch_one(&self.pool)
+    /// Reserves a commit for posting by `owner`. Only one instance can hold
+    /// the claim: it succeeds for new, pending and failed deliveries, claims
+    /// `owner` already holds, and claims abandoned for longer than
+    /// `stale_after`.
+    pub async fn claim_commit(
+        &self,
+        commit_id: i32,
+        destination: &str,
+        owner: &str,
+        stale_after: Duration,
+    ) -> Result<bool, Box<dyn Error>> {
+        let now = Utc::now();
+        let stale = now - chrono::Duration::from_std(stale_after)?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO deliveries (
+                commit_id, destination, status, attempts, claimed_by, created_at, updated_at
+            )
+            VALUES ($1, $2, 'claimed', 0, $3, datetime($4), datetime($4))
+            ON CONFLICT (commit_id, destination) DO UPDATE SET
+                status = excluded.status,
+                claimed_by = excluded.claimed_by,
+                updated_at = excluded.updated_at
+            WHERE deliveries.status IN ('pending', 'failed')
+                OR (deliveries.status = 'claimed'
+                    AND (deliveries.claimed_by = excluded.claimed_by
+                        OR deliveries.updated_at < datetime($5)))
+            "#,
+        )
+        .bind(commit_id)
+        .bind(destination)
+        .bind(owner)
+        .bind(timestamp(now))
+        .bind(timestamp(stale))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Takes or renews the lease `name` for `holder` until `ttl` from now.
+    /// Returns false while another holder's lease hasn't expired.
+    pub async fn acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<bool, Box<dyn Error>> {
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::from_std(ttl)?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO leases (name, holder, acquired_at, renewed_at, expires_at)
+            VALUES ($1, $2, datetime($3), datetime($3), datetime($4))
+            ON CONFLICT (name) DO UPDATE SET
+                holder = excluded.holder,
+                acquired_at = CASE
+                    WHEN leases.holder = excluded.holder THEN leases.acquired_at
+                    ELSE excluded.acquired_at
+                END,
+                renewed_at = excluded.renewed_at,
+                expires_at = excluded.expires_at
+            WHERE leases.holder = excluded.holder OR leases.expires_at < excluded.renewed_at
+            "#,
+        )
+        .bind(name)
+        .bind(holder)
+        .bind(timestamp(now))
+        .bind(timestamp(expires_at))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Current holder of a lease that hasn't expired yet.
+    pub async fn lease_holder(&self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let row =
+            sqlx::query("SELECT holder FROM leases WHERE name = $1 AND expires_at >= datetime($2)")
+                .bind(name)
+                .bind(timestamp(Utc::now()))
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(row.map(|row| row.get("holder")))
+    }
+
+    /// Gives up a lease early so another instance can take it over.
+    pub async fn release_lease(&self, name: &str, holder: &str) -> Result<(), Box<dyn Error>> {
+        sqlx::query("DELETE FROM leases WHERE name = $1 AND holder = $2")
+            .bind(name)
+            .bind(holder)
+            .execute(&self.pool)
             .await?;
 
-        let count: i64 = row.get("count");
-        Ok(count > 0)
+        Ok(())
     }
 
-    pub async fn mark_commit_sent(
+    async fn update_delivery(
         &self,
         commit_id: i32,
-        author: &str,
-        message: &str,
-        branch: &str,
-        changeset: &str,
+        destination: &str,
+        status: DeliveryStatus,
+        message_id: Option<&str>,
+        response_code: Option<u16>,
+        error: Option<&str>,
     ) -> Result<(), Box<dyn Error>> {
+        let now = timestamp(Utc::now());
+        let attempted = matches!(status, DeliveryStatus::Sent | DeliveryStatus::Failed);
+        let delivered_at = (status == DeliveryStatus::Sent).then_some(now.as_str());
+
         sqlx::query(
             r#"
-            INSERT INTO sent_commits (commit_id, author, message, branch, changeset)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO deliveries (
+                commit_id, destination, status, attempts, message_id, response_code,
+                last_error, created_at, updated_at, delivered_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, datetime($8), datetime($9), datetime($10))
+            ON CONFLICT (commit_id, destination) DO UPDATE SET
+                status = excluded.status,
+                attempts = deliveries.attempts + excluded.attempts,
+                message_id = COALESCE(excluded.message_id, deliveries.message_id),
+                response_code = COALESCE(excluded.response_code, deliveries.response_code),
+                last_error = excluded.last_error,
+                updated_at = excluded.updated_at,
+                delivered_at = COALESCE(excluded.delivered_at, deliveries.delivered_at)
             "#,
         )
         .bind(commit_id)
-        .bind(author)
-        .bind(message)
-        .bind(branch)
-        .bind(changeset)
+        .bind(destination)
+        .bind(status.as_str())
+        .bind(attempted as i32)
+        .bind(message_id)
+        .bind(response_code.map(i32::from))
+        .bind(error)
+        .bind(&now)
+        .bind(&now)
+        .bind(delivered_at)
         .execute(&self.pool)
         .await?;
 
-        debug!("Marked commit {} as sent", commit_id);
         Ok(())
     }
 
+    /// Finds a posted commit with the same author and normalized message made
+    /// within `window_secs` of this one, returning its Discord message and
+    /// every branch it lists.
+    pub async fn find_recent_duplicate(
+        &self,
+        commit: &CommitInfo,
+        destination: &str,
+        window_secs: u64,
+    ) -> Result<Option<(String, Vec<BranchCommit>)>, Box<dyn Error>> {
+        let Some(created_at) = commit.created_at() else {
+            return Ok(None);
+        };
+        let window = chrono::Duration::seconds(window_secs as i64);
+
+        // Commit times are stored as the feed sent them, so they're compared
+        // here rather than in SQL
+        let rows = sqlx::query(
+            r#"
+            SELECT d.message_id, c.created, datetime(c.first_seen_at) as first_seen_at
+            FROM deliveries d
+            JOIN commits c ON c.commit_id = d.commit_id
+            WHERE d.destination = $1 AND c.author = $2 AND c.message_key = $3
+                AND c.commit_id != $4 AND d.status = 'sent' AND d.message_id IS NOT NULL
+            ORDER BY c.commit_id DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(destination)
+        .bind(commit.author())
+        .bind(commit.message_key())
+        .bind(commit.id)
+        .bind(DUPLICATE_CANDIDATES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let row = rows.into_iter().find(|row| {
+            let created: String = row.get("created");
+            let first_seen_at: String = row.get("first_seen_at");
+            parse_commit_time(&created)
+                .or_else(|| parse_timestamp(&first_seen_at))
+                .is_some_and(|other| (created_at - other).abs() <= window)
+        });
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let message_id: String = row.get("message_id");
+
+        let branches = sqlx::query(
+            r#"
+            SELECT c.commit_id, c.branch, c.changeset FROM deliveries d
+            JOIN commits c ON c.commit_id = d.commit_id
+            WHERE d.destination = $1 AND d.message_id = $2
+            ORDER BY c.commit_id
+            "#,
+        )
+        .bind(destination)
+        .bind(&message_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| BranchCommit {
+            commit_id: row.get("commit_id"),
+            branch: row.get("branch"),
+            changeset: row.get("changeset"),
+        })
+        .collect();
+
+        Ok(Some((message_id, branches)))
+    }
+
+    /// Commits whose delivery failed fewer than `max_attempts` times, and
+    /// commits claimed before `stale_before` that were never finished, e.g.
+    /// because the instance posting them crashed. Oldest first.
+    pub async fn get_undelivered_commits(
+        &self,
+        destination: &str,
+        max_attempts: i32,
+        stale_before: DateTime<Utc>,
+    ) -> Result<Vec<CommitInfo>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.raw_json FROM deliveries d
+            JOIN commits c ON c.commit_id = d.commit_id
+            WHERE d.destination = $1
+                AND ((d.status = 'failed' AND d.attempts < $2)
+                    OR (d.status = 'claimed' AND d.updated_at < datetime($3)))
+            ORDER BY c.commit_id
+            "#,
+        )
+        .bind(destination)
+        .bind(max_attempts)
+        .bind(timestamp(stale_before))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut commits = Vec::with_capacity(rows.len());
+        for row in rows {
+            let raw_json: String = row.get("raw_json");
+            commits.push(serde_json::from_str(&raw_json)?);
+        }
+
+        Ok(commits)
+    }
+
+    /// Commits held back in bursts with when each is due, oldest first.
+    pub async fn get_pending_commits(
+        &self,
+        destination: &str,
+    ) -> Result<Vec<(CommitInfo, DateTime<Utc>)>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.raw_json, datetime(d.deliver_at) as deliver_at FROM deliveries d
+            JOIN commits c ON c.commit_id = d.commit_id
+            WHERE d.destination = $1 AND d.status = 'pending'
+            ORDER BY c.commit_id
+            "#,
+        )
+        .bind(destination)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut commits = Vec::with_capacity(rows.len());
+        for row in rows {
+            let raw_json: String = row.get("raw_json");
+            let deliver_at: Option<String> = row.get("deliver_at");
+            let deliver_at = deliver_at
+                .as_deref()
+                .and_then(parse_timestamp)
+                .unwrap_or_else(Utc::now);
+            commits.push((serde_json::from_str(&raw_json)?, deliver_at));
+        }
+
+        Ok(commits)
+    }
+
+    pub async fn get_deferred_commits(
+        &self,
+        destination: &str,
+    ) -> Result<Vec<CommitInfo>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.raw_json FROM deliveries d
+            JOIN commits c ON c.commit_id = d.commit_id
+            WHERE d.destination = $1 AND d.status = 'deferred'
+            ORDER BY c.commit_id
+            "#,
+        )
+        .bind(destination)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut commits = Vec::with_capacity(rows.len());
+        for row in rows {
+            let raw_json: String = row.get("raw_json");
+            commits.push(serde_json::from_str(&raw_json)?);
+        }
+
+        Ok(commits)
+    }
+
+    /// Refreshes like/dislike counts for commits we've already stored.
+    pub async fn update_reactions(&self, commits: &[CommitInfo]) -> Result<(), Box<dyn Error>> {
+        for commit in commits {
+            sqlx::query("UPDATE commits SET likes = $1, dislikes = $2 WHERE commit_id = $3")
+                .bind(commit.likes as i64)
+                .bind(commit.dislikes as i64)
+                .bind(commit.id)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_digest_report(
+        &self,
+        period: DigestPeriod,
+        until: DateTime<Utc>,
+        top_authors: usize,
+        top_commits: usize,
+    ) -> Result<DigestReport, Box<dyn Error>> {
+        let since = until - period.duration();
+        let since_str = timestamp(since);
+        let until_str = timestamp(until);
+
+        let branches = sqlx::query(
+            r#"
+            SELECT repo, branch, COUNT(*) as count FROM commits
+            WHERE first_seen_at >= datetime($1) AND first_seen_at < datetime($2)
+            GROUP BY repo, branch
+            ORDER BY count DESC, repo, branch
+            "#,
+        )
+        .bind(&since_str)
+        .bind(&until_str)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| BranchActivity {
+            repo: row.get("repo"),
+            branch: row.get("branch"),
+            commits: row.get("count"),
+        })
+        .collect::<Vec<_>>();
+
+        let authors = sqlx::query(
+            r#"
+            SELECT author, COUNT(*) as count FROM commits
+            WHERE first_seen_at >= datetime($1) AND first_seen_at < datetime($2)
+            GROUP BY author
+            ORDER BY count DESC, author
+            LIMIT $3
+            "#,
+        )
+        .bind(&since_str)
+        .bind(&until_str)
+        .bind(top_authors as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| AuthorActivity {
+            author: row.get("author"),
+            commits: row.get("count"),
+        })
+        .collect();
+
+        let commits = sqlx::query(
+            r#"
+            SELECT commit_id, author, message, likes FROM commits
+            WHERE first_seen_at >= datetime($1) AND first_seen_at < datetime($2) AND likes > 0
+            ORDER BY likes DESC, commit_id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(&since_str)
+        .bind(&until_str)
+        .bind(top_commits as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| DigestCommit {
+            commit_id: row.get("commit_id"),
+            author: row.get("author"),
+            message: row.get("message"),
+            likes: row.get("likes"),
+        })
+        .collect();
+
+        Ok(DigestReport {
+            period,
+            since,
+            until,
+            total_commits: branches.iter().map(|b| b.commits).sum(),
+            branches,
+            top_authors: authors,
+            top_commits: commits,
+        })
+    }
+
+    /// Full-text search over commit messages, authors and branches, best
+    /// matches first.
+    pub async fn search_commits(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<Vec<SearchHit>, Box<dyn Error>> {
+        let text = match self.backend {
+            Backend::Sqlite => fts5_query(&query.text),
+            Backend::Postgres => tsquery(&query.text),
+        };
+        if text.is_empty() {
+            return Err("Search text is empty".into());
+        }
+
+        // $1 is always the search text
+        let (filters, values) = filter_sql(&query.filter, 2);
+        let limit_param = values.len() + 2;
+
+        let sql = match self.backend {
+            Backend::Sqlite => format!(
+                r#"
+                SELECT c.commit_id, c.repo, c.branch, c.author, c.message,
+                    COALESCE(NULLIF(c.created, ''), datetime(c.first_seen_at)) as created,
+                    snippet(commits_fts, 0, '**', '**', '…', 16) as snippet,
+                    -bm25(commits_fts, 10.0, 2.0, 1.0) as rank
+                FROM commits_fts
+                JOIN commits c ON c.commit_id = commits_fts.rowid
+                WHERE commits_fts MATCH $1{}
+                ORDER BY rank DESC, c.commit_id DESC
+                LIMIT ${}
+                "#,
+                filters, limit_param
+            ),
+            Backend::Postgres => format!(
+                r#"
+                SELECT c.commit_id, c.repo, c.branch, c.author, c.message,
+                    COALESCE(NULLIF(c.created, ''), datetime(c.first_seen_at)) as created,
+                    ts_headline('english', c.message, q,
+                        'StartSel=**, StopSel=**, MaxFragments=1, MaxWords=16, MinWords=6')
+                        as snippet,
+                    ts_rank(c.search_vector, q)::float8 as rank
+                FROM commits c, to_tsquery('english', $1) q
+                WHERE c.search_vector @@ q{}
+                ORDER BY rank DESC, c.commit_id DESC
+                LIMIT ${}
+                "#,
+                filters, limit_param
+            ),
+        };
+
+        let mut statement = sqlx::query(&sql).bind(text);
+        for value in values {
+            statement = statement.bind(value);
+        }
+
+        let hits = statement
+            .bind(query.limit)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| SearchHit {
+                commit_id: row.get("commit_id"),
+                repo: row.get("repo"),
+                branch: row.get("branch"),
+                author: row.get("author"),
+                message: row.get("message"),
+                created: row.get("created"),
+                snippet: row.get("snippet"),
+                rank: row.get("rank"),
+            })
+            .collect();
+
+        Ok(hits)
+    }
+
+    /// Highest commit ID handled for any destination.
     pub async fn get_last_sent_commit_id(&self) -> Result<Option<i32>, Box<dyn Error>> {
-        let row = sqlx::query("SELECT MAX(commit_id) as max_id FROM sent_commits")
-            .fetch_one(&self.pool)
-            .await?;
+        let row = sqlx::query(&format!(
+            "SELECT MAX(commit_id) as max_id FROM deliveries WHERE status IN {}",
+            HANDLED_STATUSES
+        ))
+        .fetch_one(&self.pool)
+        .await?;
 
         let max_id: Option<i32> = row.get("max_id");
         Ok(max_id)
     }
 
     pub async fn get_last_sent_commit_info(&self) -> Result<Option<(i32, String)>, Box<dyn Error>> {
-        let row = sqlx::query(
-            "SELECT commit_id, changeset FROM sent_commits ORDER BY commit_id DESC LIMIT 1",
-        )
+        let row = sqlx::query(&format!(
+            "SELECT c.commit_id, c.changeset FROM commits c \
+             JOIN deliveries d ON d.commit_id = c.commit_id \
+             WHERE d.status IN {} \
+             ORDER BY c.commit_id DESC LIMIT 1",
+            HANDLED_STATUSES
+        ))
         .fetch_optional(&self.pool)
         .await?;
 
@@ -108,32 +972,704 @@ impl Database {
         }
     }
 
-    pub async fn get_sent_commits_count(&self) -> Result<i64, Box<dyn Error>> {
-        let row = sqlx::query("SELECT COUNT(*) as count FROM sent_commits")
+    /// Authors from the registry, most commits first.
+    pub async fn get_authors(&self, limit: i64) -> Result<Vec<Author>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT name, avatar, datetime(first_seen_at) as first_seen_at,
+                datetime(last_seen_at) as last_seen_at, first_commit_id, last_commit_id,
+                commit_count
+            FROM authors
+            ORDER BY commit_count DESC, name
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(author_from_row).collect())
+    }
+
+    pub async fn get_author_profile(
+        &self,
+        name: &str,
+    ) -> Result<Option<AuthorProfile>, Box<dyn Error>> {
+        let row = sqlx::query(
+            r#"
+            SELECT name, avatar, datetime(first_seen_at) as first_seen_at,
+                datetime(last_seen_at) as last_seen_at, first_commit_id, last_commit_id,
+                commit_count
+            FROM authors
+            WHERE name = $1
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let repos = sqlx::query(
+            r#"
+            SELECT repo, commit_count, datetime(first_seen_at) as first_seen_at,
+                datetime(last_seen_at) as last_seen_at
+            FROM author_repos
+            WHERE author = $1
+            ORDER BY commit_count DESC, repo
+            "#,
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| AuthorRepo {
+            repo: row.get("repo"),
+            commit_count: row.get("commit_count"),
+            first_seen_at: row.get("first_seen_at"),
+            last_seen_at: row.get("last_seen_at"),
+        })
+        .collect();
+
+        let avatars = sqlx::query(
+            "SELECT avatar, datetime(first_seen_at) as first_seen_at FROM author_avatars \
+             WHERE author = $1 ORDER BY first_seen_at, avatar",
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| AuthorAvatar {
+            avatar: row.get("avatar"),
+            first_seen_at: row.get("first_seen_at"),
+        })
+        .collect();
+
+        Ok(Some(AuthorProfile {
+            author: author_from_row(&row),
+            repos,
+            avatars,
+        }))
+    }
+
+    /// Highest commit ID stored, whether or not it was delivered.
+    pub async fn get_latest_commit_id(&self) -> Result<Option<i32>, Box<dyn Error>> {
+        let row = sqlx::query("SELECT MAX(commit_id) as max_id FROM commits")
             .fetch_one(&self.pool)
             .await?;
 
-        let count: i64 = row.get("count");
-        Ok(count)
+        Ok(row.get("max_id"))
     }
 
-    pub async fn cleanup_old_commits(&self, keep_last: i64) -> Result<(), Box<dyn Error>> {
-        // Keep only the last N commits to prevent database from growing too large
+    /// Runs of IDs missing between stored commits, from `after_id` upwards.
+    pub async fn find_commit_gaps(&self, after_id: i32) -> Result<Vec<CommitGap>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT commit_id, next_id FROM (
+                SELECT commit_id, LEAD(commit_id) OVER (ORDER BY commit_id) as next_id
+                FROM commits
+                WHERE commit_id >= $1
+            ) neighbours
+            WHERE next_id > commit_id + 1
+            ORDER BY commit_id
+            "#,
+        )
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| CommitGap {
+                first: row.get::<i32, _>("commit_id") + 1,
+                last: row.get::<i32, _>("next_id") - 1,
+            })
+            .collect())
+    }
+
+    pub async fn get_state(&self, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let row = sqlx::query("SELECT value FROM tracker_state WHERE key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|row| row.get("value")))
+    }
+
+    pub async fn set_state(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
         sqlx::query(
             r#"
-            DELETE FROM sent_commits 
-            WHERE id NOT IN (
-                SELECT id FROM sent_commits 
-                ORDER BY commit_id DESC 
-                LIMIT ?
-            )
+            INSERT INTO tracker_state (key, value, updated_at)
+            VALUES ($1, $2, datetime($3))
+            ON CONFLICT (key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = excluded.updated_at
             "#,
         )
-        .bind(keep_last)
+        .bind(key)
+        .bind(value)
+        .bind(timestamp(Utc::now()))
         .execute(&self.pool)
         .await?;
 
-        info!("Cleaned up old commits, keeping last {}", keep_last);
         Ok(())
     }
+
+    /// Number of deliveries in each status, by status name.
+    pub async fn get_delivery_counts(&self) -> Result<Vec<(String, i64)>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            "SELECT status, COUNT(*) as count FROM deliveries GROUP BY status ORDER BY status",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("status"), row.get("count")))
+            .collect())
+    }
+
+    pub async fn get_sent_commits_count(&self) -> Result<i64, Box<dyn Error>> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM commits")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let count: i64 = row.get("count");
+        Ok(count)
+    }
+
+    /// Commits dropped by `keep_last` or any retention rule, oldest first.
+    /// A `keep_last` of 0 disables the global limit.
+    pub async fn find_prunable_commits(
+        &self,
+        keep_last: i64,
+        retention: &RetentionConfig,
+    ) -> Result<Vec<i32>, Box<dyn Error>> {
+        let mut prunable = BTreeSet::new();
+
+        if keep_last > 0 {
+            let rows = sqlx::query(
+                r#"
+                SELECT commit_id FROM commits
+                WHERE commit_id NOT IN (
+                    SELECT commit_id FROM commits
+                    ORDER BY commit_id DESC
+                    LIMIT $1
+                )
+                "#,
+            )
+            .bind(keep_last)
+            .fetch_all(&self.pool)
+            .await?;
+            prunable.extend(rows.iter().map(|row| row.get::<i32, _>("commit_id")));
+        }
+
+        if retention.max_age_days > 0 {
+            let cutoff = Utc::now() - chrono::Duration::days(retention.max_age_days as i64);
+            let rows =
+                sqlx::query("SELECT commit_id FROM commits WHERE first_seen_at < datetime($1)")
+                    .bind(timestamp(cutoff))
+                    .fetch_all(&self.pool)
+                    .await?;
+            prunable.extend(rows.iter().map(|row| row.get::<i32, _>("commit_id")));
+        }
+
+        for limit in &retention.limits {
+            let mut filter = String::from("1 = 1");
+            let mut values = Vec::new();
+            for (column, value) in [("repo", &limit.repo), ("branch", &limit.branch)] {
+                if let Some(value) = value {
+                    values.push(value);
+                    filter.push_str(&format!(" AND {} = ${}", column, values.len()));
+                }
+            }
+
+            let sql = format!(
+                r#"
+                SELECT commit_id FROM commits
+                WHERE {filter} AND commit_id NOT IN (
+                    SELECT commit_id FROM commits
+                    WHERE {filter}
+                    ORDER BY commit_id DESC
+                    LIMIT ${}
+                )
+                "#,
+                values.len() + 1
+            );
+            let mut statement = sqlx::query(&sql);
+            for value in values {
+                statement = statement.bind(value);
+            }
+            let rows = statement
+                .bind(limit.keep_last)
+                .fetch_all(&self.pool)
+                .await?;
+            prunable.extend(rows.iter().map(|row| row.get::<i32, _>("commit_id")));
+        }
+
+        // Held and deferred commits haven't been posted yet, and the newest
+        // handled commit is where polling resumes from
+        let rows =
+            sqlx::query("SELECT commit_id FROM deliveries WHERE status IN ('pending', 'deferred')")
+                .fetch_all(&self.pool)
+                .await?;
+        for row in rows {
+            prunable.remove(&row.get::<i32, _>("commit_id"));
+        }
+        if let Some(last_id) = self.get_last_sent_commit_id().await? {
+            prunable.remove(&last_id);
+        }
+
+        Ok(prunable.into_iter().collect())
+    }
+
+    /// Loads commits with their delivery state, ordered by commit ID.
+    pub async fn get_commit_records(
+        &self,
+        commit_ids: &[i32],
+    ) -> Result<Vec<CommitRecord>, Box<dyn Error>> {
+        let mut records = Vec::with_capacity(commit_ids.len());
+
+        for chunk in commit_ids.chunks(ID_CHUNK_SIZE) {
+            let sql = format!(
+                "SELECT commit_id, raw_json, datetime(first_seen_at) as first_seen_at \
+                 FROM commits \
+                 WHERE commit_id IN ({}) ORDER BY commit_id",
+                placeholders(chunk.len())
+            );
+            let mut statement = sqlx::query(&sql);
+            for id in chunk {
+                statement = statement.bind(*id);
+            }
+            let commit_rows = statement.fetch_all(&self.pool).await?;
+
+            let sql = format!(
+                "SELECT commit_id, destination, status, attempts, message_id, response_code, \
+                 last_error, datetime(created_at) as created_at, \
+                 datetime(updated_at) as updated_at, datetime(delivered_at) as delivered_at \
+                 FROM deliveries \
+                 WHERE commit_id IN ({}) ORDER BY commit_id, destination",
+                placeholders(chunk.len())
+            );
+            let mut statement = sqlx::query(&sql);
+            for id in chunk {
+                statement = statement.bind(*id);
+            }
+            let delivery_rows = statement.fetch_all(&self.pool).await?;
+
+            for row in commit_rows {
+                let commit_id: i32 = row.get("commit_id");
+                let raw_json: String = row.get("raw_json");
+                let deliveries = delivery_rows
+                    .iter()
+                    .filter(|d| d.get::<i32, _>("commit_id") == commit_id)
+                    .map(|d| DeliveryRecord {
+                        destination: d.get("destination"),
+                        status: d.get("status"),
+                        attempts: d.get("attempts"),
+                        message_id: d.get("message_id"),
+                        response_code: d.get("response_code"),
+                        last_error: d.get("last_error"),
+                        created_at: d.get("created_at"),
+                        updated_at: d.get("updated_at"),
+                        delivered_at: d.get("delivered_at"),
+                    })
+                    .collect();
+
+                records.push(CommitRecord {
+                    commit: serde_json::from_str(&raw_json)?,
+                    first_seen_at: row.get("first_seen_at"),
+                    deliveries,
+                });
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Commits matching the filter with their delivery state, oldest first.
+    pub async fn export_commits(
+        &self,
+        filter: &CommitFilter,
+    ) -> Result<Vec<CommitRecord>, Box<dyn Error>> {
+        let (filters, values) = filter_sql(filter, 1);
+        let sql = format!(
+            "SELECT c.commit_id FROM commits c WHERE 1 = 1{} ORDER BY c.commit_id",
+            filters
+        );
+        let mut statement = sqlx::query(&sql);
+        for value in values {
+            statement = statement.bind(value);
+        }
+        let commit_ids: Vec<i32> = statement
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| row.get("commit_id"))
+            .collect();
+
+        self.get_commit_records(&commit_ids).await
+    }
+
+    /// Merges exported records into the database. Commits and deliveries
+    /// that already exist are kept as they are, so importing the same data
+    /// twice changes nothing.
+    pub async fn import_commits(
+        &self,
+        records: &[CommitRecord],
+    ) -> Result<ImportSummary, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+        let mut summary = ImportSummary::default();
+        let now = timestamp(Utc::now());
+
+        for record in records {
+            let commit = &record.commit;
+            let first_seen_at = if record.first_seen_at.is_empty() {
+                &now
+            } else {
+                &record.first_seen_at
+            };
+
+            if insert_commit(&mut tx, commit, first_seen_at).await? {
+                register_author(&mut tx, commit, first_seen_at).await?;
+                summary.commits += 1;
+            }
+
+            for delivery in &record.deliveries {
+                let result = sqlx::query(
+                    r#"
+                    INSERT INTO deliveries (
+                        commit_id, destination, status, attempts, message_id, response_code,
+                        last_error, created_at, updated_at, delivered_at
+                    )
+                    VALUES (
+                        $1, $2, $3, $4, $5, $6, $7, datetime($8), datetime($9), datetime($10)
+                    )
+                    ON CONFLICT (commit_id, destination) DO NOTHING
+                    "#,
+                )
+                .bind(commit.id)
+                .bind(&delivery.destination)
+                .bind(&delivery.status)
+                .bind(delivery.attempts)
+                .bind(&delivery.message_id)
+                .bind(delivery.response_code)
+                .bind(&delivery.last_error)
+                .bind(&delivery.created_at)
+                .bind(&delivery.updated_at)
+                .bind(&delivery.delivered_at)
+                .execute(&mut *tx)
+                .await?;
+                summary.deliveries += result.rows_affected();
+            }
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    /// Deletes commits and their delivery state, returning how many commits
+    /// were removed.
+    pub async fn delete_commits(&self, commit_ids: &[i32]) -> Result<u64, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+        let mut deleted = 0;
+
+        for chunk in commit_ids.chunks(ID_CHUNK_SIZE) {
+            for table in ["deliveries", "commits"] {
+                let sql = format!(
+                    "DELETE FROM {} WHERE commit_id IN ({})",
+                    table,
+                    placeholders(chunk.len())
+                );
+                let mut statement = sqlx::query(&sql);
+                for id in chunk {
+                    statement = statement.bind(*id);
+                }
+                let result = statement.execute(&mut *tx).await?;
+                if table == "commits" {
+                    deleted += result.rows_affected();
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        debug!("Deleted {} commits", deleted);
+        Ok(deleted)
+    }
+}
+
+/// Builds ` AND ...` conditions on the `c` commits alias for a filter, with
+/// parameters numbered from `first_param`. Returns the SQL and the values to
+/// bind in order.
+fn filter_sql(filter: &CommitFilter, first_param: usize) -> (String, Vec<String>) {
+    let mut sql = String::new();
+    let mut values = Vec::new();
+    let commit_date = "substr(COALESCE(NULLIF(c.created, ''), datetime(c.first_seen_at)), 1, 10)";
+    for (condition, value) in [
+        ("c.repo =".to_string(), filter.repo.clone()),
+        ("c.branch =".to_string(), filter.branch.clone()),
+        ("c.author =".to_string(), filter.author.clone()),
+        (
+            format!("{} >=", commit_date),
+            filter.since.map(|d| d.format("%Y-%m-%d").to_string()),
+        ),
+        (
+            format!("{} <=", commit_date),
+            filter.until.map(|d| d.format("%Y-%m-%d").to_string()),
+        ),
+    ] {
+        if let Some(value) = value {
+            sql.push_str(&format!(
+                " AND {} ${}",
+                condition,
+                first_param + values.len()
+            ));
+            values.push(value);
+        }
+    }
+
+    (sql, values)
+}
+
+/// Turns free text into an FTS5 query matching every word. Words are quoted
+/// so punctuation can't break the query syntax; a trailing `*` keeps its
+/// prefix-match meaning.
+fn fts5_query(text: &str) -> String {
+    text.split_whitespace()
+        .filter_map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) => (stem, "*"),
+                None => (word, ""),
+            };
+            (!word.is_empty()).then(|| format!("\"{}\"{}", word.replace('"', "\"\""), prefix))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// PostgreSQL counterpart of [`fts5_query`]: every word must match, and a
+/// trailing `*` becomes a prefix match. Anything but letters, digits and
+/// underscores separates words.
+fn tsquery(text: &str) -> String {
+    text.split_whitespace()
+        .flat_map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) => (stem, ":*"),
+                None => (word, ""),
+            };
+            let parts: Vec<&str> = word
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|part| !part.is_empty())
+                .collect();
+            let last = parts.len().saturating_sub(1);
+            parts
+                .into_iter()
+                .enumerate()
+                .map(move |(i, part)| format!("{}{}", part, if i == last { prefix } else { "" }))
+        })
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::RetentionLimit;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn commit(id: i32, author: &str, message: &str) -> CommitInfo {
+        serde_json::from_value(json!({
+            "id": id,
+            "repo": "rust_reboot",
+            "branch": "main",
+            "changeset": format!("c{}", id),
+            "created": "2025-01-01T12:00:00",
+            "likes": 0,
+            "dislikes": 0,
+            "message": message,
+            "user": { "name": author, "avatar": "" },
+        }))
+        .unwrap()
+    }
+
+    async fn database(dir: &TempDir) -> Database {
+        let url = format!("sqlite:{}", dir.path().join("commits.db").display());
+        Database::new(&url).await.unwrap()
+    }
+
+    async fn search(database: &Database, text: &str, filter: CommitFilter) -> Vec<i32> {
+        let query = SearchQuery {
+            text: text.to_string(),
+            filter,
+            limit: 10,
+        };
+        let hits = database.search_commits(&query).await.unwrap();
+        hits.iter().map(|hit| hit.commit_id).collect()
+    }
+
+    #[tokio::test]
+    async fn searches_commits_and_keeps_the_index_in_sync() {
+        let dir = TempDir::new().unwrap();
+        let database = database(&dir).await;
+        database
+            .record_commit(&commit(1, "alice", "Fix the door"))
+            .await
+            .unwrap();
+        database
+            .record_commit(&commit(2, "alice", "Add a window"))
+            .await
+            .unwrap();
+        let mut hinge = commit(3, "bob", "Fix the door hinge and the door frame");
+        hinge.branch = "staging".to_string();
+        database.record_commit(&hinge).await.unwrap();
+
+        // Stemmed, ranked by how often the words appear, with a snippet
+        let query = SearchQuery {
+            text: "doors".to_string(),
+            filter: CommitFilter::default(),
+            limit: 10,
+        };
+        let hits = database.search_commits(&query).await.unwrap();
+        let ids: Vec<i32> = hits.iter().map(|hit| hit.commit_id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert!(hits[1].snippet.contains("**door**"), "{}", hits[1].snippet);
+        assert!(hits[0].rank >= hits[1].rank);
+
+        // Every word has to match, and a trailing * matches a prefix
+        assert!(
+            search(&database, "fix window", CommitFilter::default())
+                .await
+                .is_empty()
+        );
+        assert_eq!(
+            search(&database, "win*", CommitFilter::default()).await,
+            vec![2]
+        );
+
+        // Filters narrow the matches down
+        let filter = CommitFilter {
+            author: Some("alice".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(&database, "door", filter).await, vec![1]);
+        let filter = CommitFilter {
+            branch: Some("staging".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(&database, "door", filter).await, vec![3]);
+        let filter = CommitFilter {
+            until: chrono::NaiveDate::from_ymd_opt(2024, 12, 31),
+            ..Default::default()
+        };
+        assert!(search(&database, "door", filter).await.is_empty());
+
+        // Refreshing reactions keeps the commit searchable, deleting it
+        // drops it from the index
+        database
+            .record_commit(&commit(1, "alice", "Fix the door"))
+            .await
+            .unwrap();
+        database.delete_commits(&[3]).await.unwrap();
+        assert_eq!(
+            search(&database, "door", CommitFilter::default()).await,
+            vec![1]
+        );
+
+        assert!(
+            database
+                .search_commits(&SearchQuery {
+                    text: " * ".to_string(),
+                    filter: CommitFilter::default(),
+                    limit: 10,
+                })
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn finds_commits_dropped_by_any_retention_rule() {
+        let dir = TempDir::new().unwrap();
+        let database = database(&dir).await;
+        let old = CommitRecord {
+            commit: commit(1, "alice", "First"),
+            first_seen_at: "2020-01-01 00:00:00".to_string(),
+            deliveries: Vec::new(),
+        };
+        database.import_commits(&[old]).await.unwrap();
+        for id in 2..=5 {
+            let mut commit = commit(id, "alice", "Change");
+            if id > 3 {
+                commit.branch = "staging".to_string();
+            }
+            database.record_commit(&commit).await.unwrap();
+        }
+
+        // 4 is held in a burst and 5 is where polling resumes
+        database
+            .mark_commit_pending(4, "discord", Utc::now())
+            .await
+            .unwrap();
+        database
+            .mark_commit_sent(5, "discord", Some("m5"), Some(200))
+            .await
+            .unwrap();
+
+        let prunable = |keep_last, retention| {
+            let database = &database;
+            async move {
+                database
+                    .find_prunable_commits(keep_last, &retention)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert!(prunable(0, RetentionConfig::default()).await.is_empty());
+        assert_eq!(prunable(1, RetentionConfig::default()).await, vec![1, 2, 3]);
+
+        let by_age = RetentionConfig {
+            max_age_days: 30,
+            ..Default::default()
+        };
+        assert_eq!(prunable(0, by_age).await, vec![1]);
+
+        let by_branch = RetentionConfig {
+            limits: vec![RetentionLimit {
+                repo: Some("rust_reboot".to_string()),
+                branch: Some("main".to_string()),
+                keep_last: 1,
+            }],
+            ..Default::default()
+        };
+        assert_eq!(prunable(0, by_branch).await, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn finds_runs_of_missing_commit_ids() {
+        let dir = TempDir::new().unwrap();
+        let database = database(&dir).await;
+        assert!(database.find_commit_gaps(0).await.unwrap().is_empty());
+
+        for id in [1, 2, 5, 10, 11] {
+            database
+                .record_commit(&commit(id, "alice", "Change"))
+                .await
+                .unwrap();
+        }
+
+        let gaps = database.find_commit_gaps(0).await.unwrap();
+        assert_eq!(
+            gaps,
+            vec![
+                CommitGap { first: 3, last: 4 },
+                CommitGap { first: 6, last: 9 },
+            ]
+        );
+        let gaps = database.find_commit_gaps(5).await.unwrap();
+        assert_eq!(gaps, vec![CommitGap { first: 6, last: 9 }]);
+    }
 }
@@ -1,52 +1,264 @@
-use crate::core::Config;
-use crate::models::{DiscordEmbed, EmbedAuthor, EmbedData, EmbedField, EmbedFooter};
+use crate::core::{Config, QuietWindow};
+use crate::models::{
+    BranchCommit, CommitInfo, DigestReport, DiscordEmbed, EmbedAuthor, EmbedData, EmbedField,
+    EmbedFooter, WebhookMessage,
+};
+use crate::services::mentions::{MentionMatcher, Mentions};
 use crate::services::scraper::CommitResult;
-use chrono;
+use chrono::{self, DateTime, Utc};
+use log::warn;
 use std::error::Error;
+use std::fmt;
+
+/// A message Discord accepted, with the HTTP status it answered.
+#[derive(Debug, Clone)]
+pub struct SentMessage {
+    pub id: String,
+    pub status: u16,
+}
+
+/// Discord answered a webhook call with a non-success status.
+#[derive(Debug)]
+pub struct WebhookError {
+    pub status: reqwest::StatusCode,
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Discord webhook failed with status: {}", self.status)
+    }
+}
+
+impl Error for WebhookError {}
+
+/// Name Discord deliveries are recorded under.
+pub const DISCORD_DESTINATION: &str = "discord";
+
+/// Every destination commits can be delivered to.
+pub const DESTINATIONS: &[&str] = &[DISCORD_DESTINATION];
 
 pub struct DiscordNotifier {
     client: reqwest::Client,
     config: Config,
+    mentions: MentionMatcher,
+    quiet_hours: Vec<QuietWindow>,
 }
 
 impl DiscordNotifier {
     pub fn new(config: Config) -> Self {
+        Self::with_client(config, reqwest::Client::new())
+    }
+
+    /// A notifier sending through an existing HTTP client.
+    pub fn with_client(config: Config, client: reqwest::Client) -> Self {
+        let quiet_hours = config
+            .discord
+            .quiet_hours
+            .iter()
+            .filter(|quiet_hours| quiet_hours.applies_to(DISCORD_DESTINATION))
+            .filter_map(|quiet_hours| match quiet_hours.window() {
+                Ok(window) => Some(window),
+                Err(e) => {
+                    warn!("⚠️  Ignoring quiet hours: {}", e);
+                    None
+                }
+            })
+            .collect();
+
         Self {
-            client: reqwest::Client::new(),
+            client,
+            mentions: MentionMatcher::new(&config.discord.mentions),
+            quiet_hours,
             config,
         }
     }
 
+    /// Name this notifier's deliveries are recorded under.
+    pub fn destination(&self) -> &str {
+        DISCORD_DESTINATION
+    }
+
+    /// Whether one of the quiet-hour windows for this destination is open.
+    pub fn in_quiet_hours(&self, at: DateTime<Utc>) -> bool {
+        self.quiet_hours.iter().any(|window| window.contains(at))
+    }
+
+    /// Posts a commit and returns the created Discord message. `also` holds the
+    /// same change on other branches, listed in the same post.
     pub async fn send_commit_notification(
         &self,
         result: &CommitResult,
+        also: &[CommitInfo],
+    ) -> Result<SentMessage, Box<dyn Error>> {
+        let branches: Vec<BranchCommit> = if also.is_empty() {
+            Vec::new()
+        } else {
+            std::iter::once(&result.commit)
+                .chain(also)
+                .map(BranchCommit::from)
+                .collect()
+        };
+
+        let mut mentions = self.mentions.mentions_for(&result.commit);
+        for commit in also {
+            mentions.merge(self.mentions.mentions_for(commit));
+        }
+
+        let mut payload = self.build_embed(result, &branches);
+        if !mentions.is_empty() {
+            payload.content = Some(mentions.content());
+            payload.allowed_mentions = Some(mentions.allowed_mentions());
+        }
+
+        self.post_and_wait(&payload).await
+    }
+
+    /// Posts several commits from one author on one branch as a single embed
+    /// and returns the created Discord message.
+    pub async fn send_burst_notification(
+        &self,
+        results: &[CommitResult],
+    ) -> Result<SentMessage, Box<dyn Error>> {
+        let mut mentions = Mentions::default();
+        for result in results {
+            mentions.merge(self.mentions.mentions_for(&result.commit));
+        }
+
+        let mut payload = self.build_burst_embed(results);
+        if !mentions.is_empty() {
+            payload.content = Some(mentions.content());
+            payload.allowed_mentions = Some(mentions.allowed_mentions());
+        }
+
+        self.post_and_wait(&payload).await
+    }
+
+    /// Rewrites an earlier commit post so it lists every branch the change
+    /// landed on. Edits never re-trigger mentions.
+    pub async fn edit_commit_notification(
+        &self,
+        message_id: &str,
+        result: &CommitResult,
+        branches: &[BranchCommit],
     ) -> Result<(), Box<dyn Error>> {
-        let embed = self.build_embed(result);
+        let mut url = reqwest::Url::parse(self.config.discord.webhook_url.expose())?;
+        url.path_segments_mut()
+            .map_err(|_| "Discord webhook URL cannot be edited")?
+            .pop_if_empty()
+            .push("messages")
+            .push(message_id);
+
+        let response = self
+            .client
+            .patch(url)
+            .json(&self.build_embed(result, branches))
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+
+        if !response.status().is_success() {
+            return Err(WebhookError {
+                status: response.status(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Sends commits held back during quiet hours as one grouped message.
+    pub async fn send_deferred_summary(
+        &self,
+        commits: &[CommitInfo],
+    ) -> Result<SentMessage, Box<dyn Error>> {
+        self.post_and_wait(&self.build_deferred_embed(commits))
+            .await
+    }
+
+    /// Welcomes an author the tracker has never seen commit before.
+    pub async fn send_new_contributor(&self, commit: &CommitInfo) -> Result<(), Box<dyn Error>> {
+        self.post(&self.build_new_contributor_embed(commit)).await
+    }
+
+    /// Posts a short message to check the webhook works.
+    pub async fn send_test_message(&self) -> Result<SentMessage, Box<dyn Error>> {
+        self.post_and_wait(&self.build_test_embed()).await
+    }
+
+    pub async fn send_digest(&self, report: &DigestReport) -> Result<(), Box<dyn Error>> {
+        self.post(&self.build_digest_embed(report)).await
+    }
 
+    async fn post(&self, payload: &DiscordEmbed) -> Result<(), Box<dyn Error>> {
         let response = self
             .client
-            .post(&self.config.discord.webhook_url)
+            .post(self.config.discord.webhook_url.expose())
             .header("Content-Type", "application/json")
-            .json(&embed)
+            .json(payload)
             .send()
-            .await?;
+            .await
+            .map_err(reqwest::Error::without_url)?;
 
         if !response.status().is_success() {
-            return Err(
-                format!("Discord webhook failed with status: {}", response.status()).into(),
-            );
+            return Err(WebhookError {
+                status: response.status(),
+            }
+            .into());
         }
 
         Ok(())
     }
 
-    fn build_embed(&self, result: &CommitResult) -> DiscordEmbed {
-        let commit = &result.commit;
+    /// Posts a payload and waits for Discord to return the created message.
+    async fn post_and_wait(&self, payload: &DiscordEmbed) -> Result<SentMessage, Box<dyn Error>> {
+        let response = self
+            .client
+            .post(self.config.discord.webhook_url.expose())
+            .query(&[("wait", "true")])
+            .json(payload)
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+
+        if !response.status().is_success() {
+            return Err(WebhookError {
+                status: response.status(),
+            }
+            .into());
+        }
+
+        let status = response.status().as_u16();
+        let message: WebhookMessage = response.json().await.map_err(reqwest::Error::without_url)?;
+        Ok(SentMessage {
+            id: message.id,
+            status,
+        })
+    }
+
+    fn build_burst_embed(&self, results: &[CommitResult]) -> DiscordEmbed {
+        let first = &results[0];
+        let last = &results[results.len() - 1];
+        let commit = &first.commit;
+
+        let lines = results
+            .iter()
+            .map(|r| {
+                let summary = r.commit.message.lines().next().unwrap_or_default();
+                format!(
+                    "[`{}`]({}) {}",
+                    r.commit.changeset,
+                    r.commit.link(&self.config.monitoring.commits_url),
+                    truncate(summary, 120)
+                )
+            })
+            .collect::<Vec<_>>();
 
         DiscordEmbed {
+            content: None,
+            allowed_mentions: None,
             embeds: vec![EmbedData {
-                title: "🔧 New Rust Commit".to_string(),
-                description: format!("```\n{}\n```", commit.message),
+                title: format!("🔧 {} New Rust Commits", results.len()),
+                description: join_limited(&lines, MAX_DESCRIPTION_LENGTH),
                 color: self.config.rust_color(),
                 author: EmbedAuthor {
                     name: commit.author().to_string(),
@@ -64,18 +276,14 @@ impl DiscordNotifier {
                         value: format!("`{}`", commit.branch),
                         inline: true,
                     },
-                    EmbedField {
-                        name: "🔗 Changeset".to_string(),
-                        value: format!("[`{}`]({})", commit.changeset, commit.link()),
-                        inline: true,
-                    },
                 ],
                 footer: EmbedFooter {
                     text: format!(
-                        "{} • Commit {} of {}",
+                        "{} • Commits {}–{} of {}",
                         self.config.discord.bot_name,
-                        self.format_number(result.total_commits - result.position + 1),
-                        self.format_number(result.total_commits)
+                        self.format_number(first.total_commits - first.position + 1),
+                        self.format_number(last.total_commits - last.position + 1),
+                        self.format_number(last.total_commits)
                     ),
                     icon_url: self.config.discord.bot_avatar_url.clone(),
                 },
@@ -84,6 +292,289 @@ impl DiscordNotifier {
         }
     }
 
+    /// Builds the commit embed. When `branches` is non-empty the post lists
+    /// every branch and changeset instead of only the commit's own.
+    fn build_embed(&self, result: &CommitResult, branches: &[BranchCommit]) -> DiscordEmbed {
+        let commit = &result.commit;
+
+        let (branch_field, changeset_field) = if branches.is_empty() {
+            (
+                EmbedField {
+                    name: "🌿 Branch".to_string(),
+                    value: format!("`{}`", commit.branch),
+                    inline: true,
+                },
+                EmbedField {
+                    name: "🔗 Changeset".to_string(),
+                    value: format!(
+                        "[`{}`]({})",
+                        commit.changeset,
+                        commit.link(&self.config.monitoring.commits_url)
+                    ),
+                    inline: true,
+                },
+            )
+        } else {
+            let names = branches
+                .iter()
+                .map(|b| format!("`{}`", b.branch))
+                .collect::<Vec<_>>();
+            let links = branches
+                .iter()
+                .map(|b| {
+                    format!(
+                        "[`{}`]({}) `{}`",
+                        b.changeset,
+                        b.link(&self.config.monitoring.commits_url),
+                        b.branch
+                    )
+                })
+                .collect::<Vec<_>>();
+            (
+                EmbedField {
+                    name: "🌿 Branches".to_string(),
+                    value: truncate(&names.join(", "), MAX_FIELD_LENGTH),
+                    inline: true,
+                },
+                EmbedField {
+                    name: "🔗 Changesets".to_string(),
+                    value: join_lines(&links),
+                    inline: false,
+                },
+            )
+        };
+
+        DiscordEmbed {
+            content: None,
+            allowed_mentions: None,
+            embeds: vec![EmbedData {
+                title: "🔧 New Rust Commit".to_string(),
+                description: format!("```\n{}\n```", commit.message),
+                color: self.config.rust_color(),
+                author: EmbedAuthor {
+                    name: commit.author().to_string(),
+                    url: self.config.monitoring.commits_url.clone(),
+                    icon_url: commit.avatar_url().to_string(),
+                },
+                fields: vec![
+                    EmbedField {
+                        name: "📁 Repository".to_string(),
+                        value: format!("`{}`", commit.repo),
+                        inline: true,
+                    },
+                    branch_field,
+                    changeset_field,
+                ],
+                footer: EmbedFooter {
+                    text: self.commit_footer(result),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+
+    /// Footer with the commit's place in the feed, when known.
+    fn commit_footer(&self, result: &CommitResult) -> String {
+        if result.total_commits == 0 {
+            return self.config.discord.bot_name.clone();
+        }
+
+        format!(
+            "{} • Commit {} of {}",
+            self.config.discord.bot_name,
+            self.format_number(result.total_commits - result.position + 1),
+            self.format_number(result.total_commits)
+        )
+    }
+
+    fn build_test_embed(&self) -> DiscordEmbed {
+        DiscordEmbed {
+            content: None,
+            allowed_mentions: None,
+            embeds: vec![EmbedData {
+                title: "✅ Webhook test".to_string(),
+                description: format!("{} can post to this channel.", self.config.discord.bot_name),
+                color: self.config.rust_color(),
+                author: EmbedAuthor {
+                    name: self.config.discord.bot_name.clone(),
+                    url: self.config.monitoring.commits_url.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                fields: Vec::new(),
+                footer: EmbedFooter {
+                    text: self.config.discord.bot_name.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+
+    fn build_new_contributor_embed(&self, commit: &CommitInfo) -> DiscordEmbed {
+        let summary = commit.message.lines().next().unwrap_or_default();
+
+        DiscordEmbed {
+            content: None,
+            allowed_mentions: None,
+            embeds: vec![EmbedData {
+                title: format!("🎉 New contributor: {}", commit.author()),
+                description: format!(
+                    "First commit to `{}` on `{}`: [`{}`]({})\n{}",
+                    commit.repo,
+                    commit.branch,
+                    commit.changeset,
+                    commit.link(&self.config.monitoring.commits_url),
+                    truncate(summary, 200)
+                ),
+                color: self.config.rust_color(),
+                author: EmbedAuthor {
+                    name: commit.author().to_string(),
+                    url: self.config.monitoring.commits_url.clone(),
+                    icon_url: commit.avatar_url().to_string(),
+                },
+                fields: Vec::new(),
+                footer: EmbedFooter {
+                    text: self.config.discord.bot_name.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+
+    fn build_deferred_embed(&self, commits: &[CommitInfo]) -> DiscordEmbed {
+        let mut mentions = Mentions::default();
+        for commit in commits {
+            mentions.merge(self.mentions.mentions_for(commit));
+        }
+
+        let lines = commits
+            .iter()
+            .map(|c| {
+                let summary = c.message.lines().next().unwrap_or_default();
+                format!(
+                    "[`{}`]({}) `{}` **{}**: {}",
+                    c.changeset,
+                    c.link(&self.config.monitoring.commits_url),
+                    c.branch,
+                    c.author(),
+                    truncate(summary, 100)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        DiscordEmbed {
+            content: (!mentions.is_empty()).then(|| mentions.content()),
+            allowed_mentions: (!mentions.is_empty()).then(|| mentions.allowed_mentions()),
+            embeds: vec![EmbedData {
+                title: format!(
+                    "🌙 {} commits during quiet hours",
+                    self.format_number(commits.len() as u32)
+                ),
+                description: join_limited(&lines, MAX_DESCRIPTION_LENGTH),
+                color: self.config.rust_color(),
+                author: EmbedAuthor {
+                    name: self.config.discord.bot_name.clone(),
+                    url: self.config.monitoring.commits_url.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                fields: Vec::new(),
+                footer: EmbedFooter {
+                    text: self.config.discord.bot_name.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+
+    fn build_digest_embed(&self, report: &DigestReport) -> DiscordEmbed {
+        let mut fields = Vec::new();
+
+        if !report.branches.is_empty() {
+            let lines = report
+                .branches
+                .iter()
+                .map(|b| format!("`{}` / `{}` — **{}**", b.repo, b.branch, b.commits))
+                .collect::<Vec<_>>();
+            fields.push(EmbedField {
+                name: "🌿 Commits by Branch".to_string(),
+                value: join_lines(&lines),
+                inline: false,
+            });
+        }
+
+        if !report.top_authors.is_empty() {
+            let lines = report
+                .top_authors
+                .iter()
+                .enumerate()
+                .map(|(i, a)| format!("{}. {} — **{}**", i + 1, a.author, a.commits))
+                .collect::<Vec<_>>();
+            fields.push(EmbedField {
+                name: "👤 Top Authors".to_string(),
+                value: join_lines(&lines),
+                inline: false,
+            });
+        }
+
+        if !report.top_commits.is_empty() {
+            let lines = report
+                .top_commits
+                .iter()
+                .map(|c| {
+                    let summary = c.message.lines().next().unwrap_or_default();
+                    format!(
+                        "👍 {} — [#{}]({}) {}: {}",
+                        c.likes,
+                        c.commit_id,
+                        c.link(&self.config.monitoring.commits_url),
+                        c.author,
+                        truncate(summary, 80)
+                    )
+                })
+                .collect::<Vec<_>>();
+            fields.push(EmbedField {
+                name: "⭐ Most Liked".to_string(),
+                value: join_lines(&lines),
+                inline: false,
+            });
+        }
+
+        let description = if report.total_commits == 0 {
+            "No new commits in this period.".to_string()
+        } else {
+            format!(
+                "**{}** commits between {} and {}",
+                self.format_number(report.total_commits as u32),
+                report.since.format("%Y-%m-%d %H:%M UTC"),
+                report.until.format("%Y-%m-%d %H:%M UTC")
+            )
+        };
+
+        DiscordEmbed {
+            content: None,
+            allowed_mentions: None,
+            embeds: vec![EmbedData {
+                title: format!("📰 {} Rust Commit Digest", report.period.label()),
+                description,
+                color: self.config.rust_color(),
+                author: EmbedAuthor {
+                    name: self.config.discord.bot_name.clone(),
+                    url: self.config.monitoring.commits_url.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                fields,
+                footer: EmbedFooter {
+                    text: self.config.discord.bot_name.clone(),
+                    icon_url: self.config.discord.bot_avatar_url.clone(),
+                },
+                timestamp: report.until.to_rfc3339(),
+            }],
+        }
+    }
+
     fn format_number(&self, num: u32) -> String {
         // Add commas to large numbers for readability
         let num_str = num.to_string();
@@ -91,7 +582,7 @@ impl DiscordNotifier {
         let mut result = String::new();
 
         for (i, &ch) in chars.iter().enumerate() {
-            if i > 0 && (chars.len() - i) % 3 == 0 {
+            if i > 0 && (chars.len() - i).is_multiple_of(3) {
                 result.push(',');
             }
             result.push(ch);
@@ -100,3 +591,75 @@ impl DiscordNotifier {
         result
     }
 }
+
+/// Discord rejects embed field values longer than 1024 characters.
+const MAX_FIELD_LENGTH: usize = 1024;
+/// Discord rejects embed descriptions longer than 4096 characters.
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+fn join_lines(lines: &[String]) -> String {
+    join_limited(lines, MAX_FIELD_LENGTH)
+}
+
+fn join_limited(lines: &[String], max_length: usize) -> String {
+    let mut value = String::new();
+
+    for line in lines {
+        // Leave room for the newline and the ellipsis
+        if value.len() + line.len() + 2 > max_length {
+            value.push('…');
+            break;
+        }
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(line);
+    }
+
+    value
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let truncated: String = text.chars().take(max_chars - 1).collect();
+        format!("{}…", truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DigestCommit, DigestPeriod};
+
+    #[test]
+    fn links_digest_commits_on_the_configured_site() {
+        let mut config = Config::default();
+        config.monitoring.commits_url = "https://mirror.example.com/feed.json".to_string();
+        let notifier = DiscordNotifier::new(config);
+        let now = Utc::now();
+        let report = DigestReport {
+            period: DigestPeriod::Daily,
+            since: now - chrono::Duration::days(1),
+            until: now,
+            total_commits: 1,
+            branches: Vec::new(),
+            top_authors: Vec::new(),
+            top_commits: vec![DigestCommit {
+                commit_id: 7,
+                author: "Alistair".to_string(),
+                message: "Fixed the door".to_string(),
+                likes: 3,
+            }],
+        };
+
+        let embed = notifier.build_digest_embed(&report);
+        let liked = &embed.embeds[0].fields[0].value;
+        assert!(
+            liked.contains("[#7](https://mirror.example.com/7)"),
+            "{}",
+            liked
+        );
+    }
+}
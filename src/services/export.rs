@@ -0,0 +1,317 @@
+use crate::models::{CommitInfo, CommitRecord, CommitUser, DeliveryRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{BufRead, Read, Write};
+use std::str::FromStr;
+
+/// File formats for exported commits. JSON and NDJSON carry the full
+/// records; CSV has one row per delivery so it opens cleanly in a
+/// spreadsheet, and drops feed fields the tracker doesn't model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Guesses the format from a file name, ignoring a trailing `.gz`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = path.strip_suffix(".gz").unwrap_or(path);
+        let extension = path.rsplit_once('.')?.1;
+        extension.parse().ok()
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(format!(
+                "Unknown format '{}'. Expected json, ndjson or csv",
+                s
+            )),
+        }
+    }
+}
+
+/// A commit and one of its deliveries flattened into a CSV row. Commits
+/// without deliveries get a single row with the delivery columns empty.
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    commit_id: i32,
+    repo: String,
+    branch: String,
+    changeset: String,
+    created: String,
+    author: String,
+    avatar: String,
+    message: String,
+    likes: u32,
+    dislikes: u32,
+    first_seen_at: String,
+    destination: Option<String>,
+    status: Option<String>,
+    attempts: Option<i32>,
+    message_id: Option<String>,
+    response_code: Option<i32>,
+    last_error: Option<String>,
+    delivery_created_at: Option<String>,
+    delivery_updated_at: Option<String>,
+    delivered_at: Option<String>,
+}
+
+impl CsvRow {
+    fn new(record: &CommitRecord, delivery: Option<&DeliveryRecord>) -> Self {
+        let commit = &record.commit;
+        Self {
+            commit_id: commit.id,
+            repo: commit.repo.clone(),
+            branch: commit.branch.clone(),
+            changeset: commit.changeset.clone(),
+            created: commit.created.clone(),
+            author: commit.author().to_string(),
+            avatar: commit.avatar_url().to_string(),
+            message: commit.message.clone(),
+            likes: commit.likes,
+            dislikes: commit.dislikes,
+            first_seen_at: record.first_seen_at.clone(),
+            destination: delivery.map(|d| d.destination.clone()),
+            status: delivery.map(|d| d.status.clone()),
+            attempts: delivery.map(|d| d.attempts),
+            message_id: delivery.and_then(|d| d.message_id.clone()),
+            response_code: delivery.and_then(|d| d.response_code),
+            last_error: delivery.and_then(|d| d.last_error.clone()),
+            delivery_created_at: delivery.map(|d| d.created_at.clone()),
+            delivery_updated_at: delivery.map(|d| d.updated_at.clone()),
+            delivered_at: delivery.and_then(|d| d.delivered_at.clone()),
+        }
+    }
+
+    fn into_record(self) -> (CommitRecord, Option<DeliveryRecord>) {
+        let delivery = match (self.destination, self.status) {
+            (Some(destination), Some(status)) => Some(DeliveryRecord {
+                destination,
+                status,
+                attempts: self.attempts.unwrap_or_default(),
+                message_id: self.message_id,
+                response_code: self.response_code,
+                last_error: self.last_error,
+                created_at: self.delivery_created_at.unwrap_or_default(),
+                updated_at: self.delivery_updated_at.unwrap_or_default(),
+                delivered_at: self.delivered_at,
+            }),
+            _ => None,
+        };
+
+        let record = CommitRecord {
+            commit: CommitInfo {
+                id: self.commit_id,
+                repo: self.repo,
+                branch: self.branch,
+                changeset: self.changeset,
+                created: self.created,
+                likes: self.likes,
+                dislikes: self.dislikes,
+                message: self.message,
+                user: CommitUser {
+                    name: self.author,
+                    avatar: self.avatar,
+                },
+                extra: serde_json::Map::new(),
+            },
+            first_seen_at: self.first_seen_at,
+            deliveries: Vec::new(),
+        };
+
+        (record, delivery)
+    }
+}
+
+pub fn write_records<W: Write>(
+    writer: W,
+    format: ExportFormat,
+    records: &[CommitRecord],
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        ExportFormat::Json => serde_json::to_writer_pretty(writer, records)?,
+        ExportFormat::Ndjson => {
+            let mut writer = writer;
+            for record in records {
+                serde_json::to_writer(&mut writer, record)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for record in records {
+                if record.deliveries.is_empty() {
+                    writer.serialize(CsvRow::new(record, None))?;
+                }
+                for delivery in &record.deliveries {
+                    writer.serialize(CsvRow::new(record, Some(delivery)))?;
+                }
+            }
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_records<R: BufRead>(
+    reader: R,
+    format: ExportFormat,
+) -> Result<Vec<CommitRecord>, Box<dyn Error>> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::from_reader(reader)?),
+        ExportFormat::Ndjson => {
+            let mut records = Vec::new();
+            for (index, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record = serde_json::from_str(&line)
+                    .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+                records.push(record);
+            }
+            Ok(records)
+        }
+        ExportFormat::Csv => read_csv(reader),
+    }
+}
+
+/// Folds CSV rows back into records; rows of the same commit are grouped.
+fn read_csv<R: Read>(reader: R) -> Result<Vec<CommitRecord>, Box<dyn Error>> {
+    let mut records: Vec<CommitRecord> = Vec::new();
+    let mut positions = HashMap::new();
+
+    for row in csv::Reader::from_reader(reader).deserialize::<CsvRow>() {
+        let (record, delivery) = row?.into_record();
+        let index = *positions.entry(record.commit.id).or_insert_with(|| {
+            records.push(record);
+            records.len() - 1
+        });
+        records[index].deliveries.extend(delivery);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CommitFilter;
+    use crate::services::Database;
+    use serde_json::json;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    async fn database(dir: &TempDir, name: &str) -> Database {
+        let url = format!("sqlite:{}", dir.path().join(name).display());
+        Database::new(&url).await.unwrap()
+    }
+
+    fn commit(id: i32, message: &str) -> CommitInfo {
+        serde_json::from_value(json!({
+            "id": id,
+            "repo": "rust_reboot",
+            "branch": "main",
+            "changeset": format!("c{}", id),
+            "created": "2025-01-01T12:00:00",
+            "likes": 2,
+            "dislikes": 1,
+            "message": message,
+            "user": { "name": "alice", "avatar": "https://example.com/alice.png" },
+        }))
+        .unwrap()
+    }
+
+    /// Records without the feed fields CSV drops, for comparing.
+    fn summary(records: &[CommitRecord]) -> Vec<String> {
+        records
+            .iter()
+            .map(|r| {
+                let deliveries: Vec<String> = r
+                    .deliveries
+                    .iter()
+                    .map(|d| format!("{}={}/{:?}", d.destination, d.status, d.message_id))
+                    .collect();
+                format!(
+                    "{} {} {:?} {} {:?}",
+                    r.commit.id,
+                    r.commit.author(),
+                    r.commit.message,
+                    r.first_seen_at,
+                    deliveries
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn guesses_the_format_from_the_file_name() {
+        assert_eq!(
+            ExportFormat::from_path("commits.jsonl.gz"),
+            Some(ExportFormat::Ndjson)
+        );
+        assert_eq!(
+            ExportFormat::from_path("out/commits.CSV"),
+            Some(ExportFormat::Csv)
+        );
+        assert_eq!(ExportFormat::from_path("commits"), None);
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn round_trips_commits_through_every_format() {
+        let dir = TempDir::new().unwrap();
+        let source = database(&dir, "source.db").await;
+        source
+            .record_commit(&commit(1, "Fix the door"))
+            .await
+            .unwrap();
+        source
+            .record_commit(&commit(2, "Quote \"this\",\nthen wrap"))
+            .await
+            .unwrap();
+        source
+            .mark_commit_sent(1, "discord", Some("m1"), Some(200))
+            .await
+            .unwrap();
+        let exported = source
+            .export_commits(&CommitFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(exported.len(), 2);
+
+        for (index, format) in [ExportFormat::Json, ExportFormat::Ndjson, ExportFormat::Csv]
+            .into_iter()
+            .enumerate()
+        {
+            let mut buffer = Vec::new();
+            write_records(&mut buffer, format, &exported).unwrap();
+            let records = read_records(Cursor::new(buffer), format).unwrap();
+            assert_eq!(summary(&records), summary(&exported), "{:?}", format);
+
+            // Importing is idempotent
+            let target = database(&dir, &format!("target{}.db", index)).await;
+            let imported = target.import_commits(&records).await.unwrap();
+            assert_eq!((imported.commits, imported.deliveries), (2, 1));
+            let imported = target.import_commits(&records).await.unwrap();
+            assert_eq!((imported.commits, imported.deliveries), (0, 0));
+
+            let reexported = target
+                .export_commits(&CommitFilter::default())
+                .await
+                .unwrap();
+            assert_eq!(summary(&reexported), summary(&exported), "{:?}", format);
+        }
+    }
+}
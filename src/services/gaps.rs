@@ -0,0 +1,168 @@
+use crate::models::CommitGap;
+use crate::services::{CommitResult, CommitScraper};
+use log::debug;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Outcome of checking gaps against the public feed.
+#[derive(Debug, Default)]
+pub struct GapScan {
+    /// Commits the feed shows that were never stored, oldest first
+    pub missed: Vec<CommitResult>,
+    /// IDs the feed doesn't show, e.g. commits to private repositories
+    pub hidden: i64,
+    /// Parts of gaps older than the pages searched
+    pub unresolved: Vec<CommitGap>,
+}
+
+/// Pages back through the feed until every gap is covered or `max_pages`
+/// have been read. IDs inside a covered gap that the feed doesn't list are
+/// counted as hidden.
+pub async fn resolve_gaps(
+    scraper: &CommitScraper,
+    url: &str,
+    gaps: &[CommitGap],
+    max_pages: u32,
+) -> Result<GapScan, Box<dyn Error>> {
+    let Some(lowest) = gaps.iter().map(|gap| gap.first).min() else {
+        return Ok(GapScan::default());
+    };
+
+    let mut found = BTreeMap::new();
+    let mut oldest_seen = i32::MAX;
+    for page in 1..=max_pages {
+        let results = scraper.fetch_page(url, page).await?;
+        if results.is_empty() {
+            break;
+        }
+
+        for result in results {
+            oldest_seen = oldest_seen.min(result.commit.id);
+            if gaps.iter().any(|gap| gap.contains(result.commit.id)) {
+                // New commits shift pages while we read, so the same commit
+                // can show up twice
+                found.entry(result.commit.id).or_insert(result);
+            }
+        }
+
+        debug!(
+            "Gap scan read page {}, oldest commit #{}",
+            page, oldest_seen
+        );
+        if oldest_seen <= lowest {
+            break;
+        }
+    }
+
+    let mut scan = GapScan::default();
+    for gap in gaps {
+        if oldest_seen > gap.last {
+            scan.unresolved.push(*gap);
+            continue;
+        }
+
+        let covered = CommitGap {
+            first: gap.first.max(oldest_seen),
+            last: gap.last,
+        };
+        if covered.first > gap.first {
+            scan.unresolved.push(CommitGap {
+                first: gap.first,
+                last: covered.first - 1,
+            });
+        }
+
+        let missed = found.range(covered.first..=covered.last).count() as i64;
+        scan.hidden += covered.size() - missed;
+    }
+    scan.missed = found.into_values().collect();
+
+    Ok(scan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn commit(id: i32) -> Value {
+        json!({
+            "id": id,
+            "repo": "rust_reboot",
+            "branch": "main",
+            "changeset": format!("c{}", id),
+            "created": "2025-01-01T12:00:00",
+            "likes": 0,
+            "dislikes": 0,
+            "message": "Change",
+            "user": { "name": "alice", "avatar": "" },
+        })
+    }
+
+    /// Serves the feed three commits to a page, newest first.
+    async fn serve_pages(server: &MockServer, pages: &[&[i32]]) {
+        for (index, ids) in pages.iter().enumerate() {
+            let results: Vec<Value> = ids.iter().map(|id| commit(*id)).collect();
+            Mock::given(method("GET"))
+                .and(path("/feed"))
+                .and(query_param("p", (index + 1).to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "total": 100,
+                    "skip": index * 3,
+                    "take": 3,
+                    "results": results,
+                })))
+                .mount(server)
+                .await;
+        }
+    }
+
+    fn ids(scan: &GapScan) -> Vec<i32> {
+        scan.missed.iter().map(|result| result.commit.id).collect()
+    }
+
+    const GAPS: [CommitGap; 2] = [
+        CommitGap { first: 3, last: 4 },
+        CommitGap { first: 6, last: 9 },
+    ];
+
+    #[tokio::test]
+    async fn recovers_missed_commits_and_counts_hidden_ones() {
+        let server = MockServer::start().await;
+        serve_pages(&server, &[&[10, 9, 7], &[6, 5, 3], &[2, 1]]).await;
+        let url = format!("{}/feed", server.uri());
+
+        let scan = resolve_gaps(&CommitScraper::new().unwrap(), &url, &GAPS, 5)
+            .await
+            .unwrap();
+        assert_eq!(ids(&scan), vec![3, 6, 7, 9]);
+        assert_eq!(scan.hidden, 2);
+        assert!(scan.unresolved.is_empty());
+
+        // Stops once the oldest gap is covered
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn leaves_gaps_older_than_the_pages_read() {
+        let server = MockServer::start().await;
+        serve_pages(&server, &[&[10, 9, 7], &[6, 5, 3]]).await;
+        let url = format!("{}/feed", server.uri());
+
+        let scan = resolve_gaps(&CommitScraper::new().unwrap(), &url, &GAPS, 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(&scan), vec![7, 9]);
+        assert_eq!(scan.hidden, 1);
+        assert_eq!(
+            scan.unresolved,
+            vec![
+                CommitGap { first: 3, last: 4 },
+                CommitGap { first: 6, last: 6 },
+            ]
+        );
+    }
+}
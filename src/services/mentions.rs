@@ -0,0 +1,263 @@
+use crate::core::MentionRule;
+use crate::models::{AllowedMentions, CommitInfo};
+use log::warn;
+use regex::Regex;
+
+struct CompiledRule {
+    message: Option<Regex>,
+    repo: Option<Regex>,
+    branch: Option<Regex>,
+    author: Option<Regex>,
+    roles: Vec<String>,
+    users: Vec<String>,
+}
+
+/// Roles and users to ping for a single commit.
+#[derive(Debug, Default)]
+pub struct Mentions {
+    pub roles: Vec<String>,
+    pub users: Vec<String>,
+}
+
+pub struct MentionMatcher {
+    rules: Vec<CompiledRule>,
+}
+
+impl MentionMatcher {
+    pub fn new(rules: &[MentionRule]) -> Self {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| match Self::compile(rule) {
+                Ok(compiled) => Some(compiled),
+                Err(e) => {
+                    warn!("⚠️  Skipping mention rule #{}: {}", index + 1, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    fn compile(rule: &MentionRule) -> Result<CompiledRule, regex::Error> {
+        let compile = |pattern: &Option<String>| pattern.as_deref().map(Regex::new).transpose();
+
+        Ok(CompiledRule {
+            message: compile(&rule.message)?,
+            repo: compile(&rule.repo)?,
+            branch: compile(&rule.branch)?,
+            author: compile(&rule.author)?,
+            roles: rule.roles.clone(),
+            users: rule.users.clone(),
+        })
+    }
+
+    pub fn mentions_for(&self, commit: &CommitInfo) -> Mentions {
+        let mut mentions = Mentions::default();
+
+        for rule in self.rules.iter().filter(|rule| rule.matches(commit)) {
+            mentions.add(&rule.roles, &rule.users);
+        }
+
+        mentions
+    }
+}
+
+impl CompiledRule {
+    fn matches(&self, commit: &CommitInfo) -> bool {
+        let checks = [
+            (&self.message, commit.message.as_str()),
+            (&self.repo, commit.repo.as_str()),
+            (&self.branch, commit.branch.as_str()),
+            (&self.author, commit.author()),
+        ];
+
+        let mut any_pattern = false;
+        for (pattern, value) in checks {
+            if let Some(regex) = pattern {
+                if !regex.is_match(value) {
+                    return false;
+                }
+                any_pattern = true;
+            }
+        }
+
+        any_pattern
+    }
+}
+
+impl Mentions {
+    fn add(&mut self, roles: &[String], users: &[String]) {
+        for role in roles {
+            if !self.roles.contains(role) {
+                self.roles.push(role.clone());
+            }
+        }
+        for user in users {
+            if !self.users.contains(user) {
+                self.users.push(user.clone());
+            }
+        }
+    }
+
+    /// Combines mentions from several commits into one ping list.
+    pub fn merge(&mut self, other: Mentions) {
+        self.add(&other.roles, &other.users);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roles.is_empty() && self.users.is_empty()
+    }
+
+    /// Message content that pings every role and user.
+    pub fn content(&self) -> String {
+        self.roles
+            .iter()
+            .map(|id| format!("<@&{}>", id))
+            .chain(self.users.iter().map(|id| format!("<@{}>", id)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Restricts Discord to pinging exactly these roles and users, so that
+    /// `@everyone` or stray mentions in commit messages never fire.
+    pub fn allowed_mentions(&self) -> AllowedMentions {
+        AllowedMentions {
+            parse: Vec::new(),
+            roles: self.roles.clone(),
+            users: self.users.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CommitUser;
+
+    fn commit(repo: &str, branch: &str, message: &str) -> CommitInfo {
+        CommitInfo {
+            id: 1,
+            repo: repo.to_string(),
+            branch: branch.to_string(),
+            changeset: "abc".to_string(),
+            created: String::new(),
+            likes: 0,
+            dislikes: 0,
+            message: message.to_string(),
+            user: CommitUser {
+                name: "Alistair".to_string(),
+                avatar: String::new(),
+            },
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    fn rule(toml: &str) -> MentionRule {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn matches_keywords_ignoring_case() {
+        let matcher = MentionMatcher::new(&[rule(
+            r#"
+            message = "(?i)modding|oxide|harmony"
+            roles = ["1"]
+            "#,
+        )]);
+
+        for message in ["Fixed Oxide hooks", "HARMONY patches", "modding api"] {
+            let mentions = matcher.mentions_for(&commit("rust_reboot", "main", message));
+            assert_eq!(mentions.roles, ["1"], "{}", message);
+        }
+        assert!(
+            matcher
+                .mentions_for(&commit("rust_reboot", "main", "Fixed the door"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn needs_every_pattern_on_a_rule_to_match() {
+        let matcher = MentionMatcher::new(&[rule(
+            r#"
+            repo = "^rust_reboot$"
+            branch = "^main$"
+            users = ["2"]
+            "#,
+        )]);
+
+        let matching = matcher.mentions_for(&commit("rust_reboot", "main", "Anything"));
+        assert_eq!(matching.users, ["2"]);
+        for (repo, branch) in [("rust_reboot", "experimental"), ("sbox", "main")] {
+            assert!(
+                matcher
+                    .mentions_for(&commit(repo, branch, "Anything"))
+                    .is_empty()
+            );
+        }
+    }
+
+    #[test]
+    fn skips_rules_without_patterns_or_with_invalid_ones() {
+        let matcher = MentionMatcher::new(&[
+            rule(r#"roles = ["1"]"#),
+            rule(
+                r#"
+                message = "("
+                roles = ["2"]
+                "#,
+            ),
+        ]);
+
+        assert!(
+            matcher
+                .mentions_for(&commit("rust_reboot", "main", "("))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn pings_roles_and_users_once_each() {
+        let matcher = MentionMatcher::new(&[
+            rule(
+                r#"
+                message = "EAC"
+                roles = ["10"]
+                users = ["20"]
+                "#,
+            ),
+            rule(
+                r#"
+                message = "EAC"
+                roles = ["10", "11"]
+                "#,
+            ),
+        ]);
+
+        let mentions = matcher.mentions_for(&commit("rust_reboot", "main", "EAC update"));
+        assert_eq!(mentions.content(), "<@&10> <@&11> <@20>");
+
+        let allowed = serde_json::to_value(mentions.allowed_mentions()).unwrap();
+        assert_eq!(
+            allowed,
+            serde_json::json!({ "parse": [], "roles": ["10", "11"], "users": ["20"] })
+        );
+    }
+
+    #[test]
+    fn merges_mentions_from_several_commits() {
+        let mut mentions = Mentions {
+            roles: vec!["1".to_string()],
+            users: Vec::new(),
+        };
+        mentions.merge(Mentions {
+            roles: vec!["1".to_string(), "2".to_string()],
+            users: vec!["3".to_string()],
+        });
+
+        assert_eq!(mentions.roles, ["1", "2"]);
+        assert_eq!(mentions.users, ["3"]);
+    }
+}
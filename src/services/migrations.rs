@@ -0,0 +1,408 @@
+use crate::services::database::Backend;
+use log::{debug, info};
+use sqlx::{AnyPool, Row};
+use std::error::Error;
+
+/// A schema change embedded in the binary, applied once in version order.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "sent commits",
+        sql: include_str!("../../migrations/sqlite/0001_sent_commits.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "commit repo and reactions",
+        sql: include_str!("../../migrations/sqlite/0002_commit_details.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "deferred commits",
+        sql: include_str!("../../migrations/sqlite/0003_deferred_commits.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "duplicate tracking",
+        sql: include_str!("../../migrations/sqlite/0004_duplicate_tracking.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "commits and deliveries",
+        sql: include_str!("../../migrations/sqlite/0005_commits_and_deliveries.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "commit search",
+        sql: include_str!("../../migrations/sqlite/0006_commit_search.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "claims and leases",
+        sql: include_str!("../../migrations/sqlite/0007_claims_and_leases.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "tracker state",
+        sql: include_str!("../../migrations/sqlite/0008_tracker_state.sql"),
+    },
+    Migration {
+        version: 9,
+        description: "authors",
+        sql: include_str!("../../migrations/sqlite/0009_authors.sql"),
+    },
+    Migration {
+        version: 10,
+        description: "pending bursts",
+        sql: include_str!("../../migrations/sqlite/0010_pending_bursts.sql"),
+    },
+    Migration {
+        version: 11,
+        description: "timestamps",
+        sql: include_str!("../../migrations/sqlite/0011_timestamps.sql"),
+    },
+];
+
+/// PostgreSQL migrations share version numbers with SQLite so both backends
+/// report the same schema version for the same query surface.
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 5,
+        description: "commits and deliveries",
+        sql: include_str!("../../migrations/postgres/0005_commits_and_deliveries.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "commit search",
+        sql: include_str!("../../migrations/postgres/0006_commit_search.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "claims and leases",
+        sql: include_str!("../../migrations/postgres/0007_claims_and_leases.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "tracker state",
+        sql: include_str!("../../migrations/postgres/0008_tracker_state.sql"),
+    },
+    Migration {
+        version: 9,
+        description: "authors",
+        sql: include_str!("../../migrations/postgres/0009_authors.sql"),
+    },
+    Migration {
+        version: 10,
+        description: "pending bursts",
+        sql: include_str!("../../migrations/postgres/0010_pending_bursts.sql"),
+    },
+    Migration {
+        version: 11,
+        description: "timestamps",
+        sql: include_str!("../../migrations/postgres/0011_timestamps.sql"),
+    },
+];
+
+pub fn migrations(backend: Backend) -> &'static [Migration] {
+    match backend {
+        Backend::Sqlite => SQLITE_MIGRATIONS,
+        Backend::Postgres => POSTGRES_MIGRATIONS,
+    }
+}
+
+/// Newest schema version this binary knows about.
+pub fn latest_version(backend: Backend) -> i64 {
+    migrations(backend).last().map_or(0, |m| m.version)
+}
+
+/// Brings the database up to the latest schema, one transaction per
+/// migration. Refuses to touch databases written by a newer binary.
+pub async fn run(pool: &AnyPool, backend: Backend) -> Result<(), Box<dyn Error>> {
+    let create_table = match backend {
+        Backend::Sqlite => {
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#
+        }
+        Backend::Postgres => {
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_version (
+                version BIGINT PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TIMESTAMPTZ DEFAULT now()
+            )
+            "#
+        }
+    };
+    sqlx::query(create_table).execute(pool).await?;
+
+    let mut current = current_version(pool).await?;
+    if current == 0 && backend == Backend::Sqlite {
+        current = adopt_legacy_schema(pool).await?;
+    }
+
+    let latest = latest_version(backend);
+    if current > latest {
+        return Err(format!(
+            "❌ Database schema version {} is newer than this binary supports ({}).\n\
+            Please upgrade rust-commit-tracker before using this database.",
+            current, latest
+        )
+        .into());
+    }
+
+    for migration in migrations(backend).iter().filter(|m| m.version > current) {
+        let mut tx = pool.begin().await?;
+
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        info!(
+            "🗃️  Applied database migration {} ({})",
+            migration.version, migration.description
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn current_version(pool: &AnyPool) -> Result<i64, Box<dyn Error>> {
+    let row = sqlx::query("SELECT COALESCE(MAX(version), 0) as version FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.get("version"))
+}
+
+/// Databases created before migrations existed have tables but no recorded
+/// version. Works out how far they got from their columns and records every
+/// migration up to that point as applied.
+async fn adopt_legacy_schema(pool: &AnyPool) -> Result<i64, Box<dyn Error>> {
+    if !table_exists(pool, "sent_commits").await? {
+        return Ok(0);
+    }
+
+    let version = if column_exists(pool, "sent_commits", "message_id").await? {
+        4
+    } else if table_exists(pool, "deferred_commits").await? {
+        3
+    } else if column_exists(pool, "sent_commits", "repo").await? {
+        2
+    } else {
+        1
+    };
+
+    let mut tx = pool.begin().await?;
+    // Columns were added one by one before migrations existed, but the
+    // index migration 4 creates never was
+    if version >= 4 {
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_sent_commits_duplicates \
+             ON sent_commits (author, message_key, sent_at)",
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    for migration in SQLITE_MIGRATIONS.iter().filter(|m| m.version <= version) {
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    debug!("Adopted existing database at schema version {}", version);
+    Ok(version)
+}
+
+async fn table_exists(pool: &AnyPool, table: &str) -> Result<bool, Box<dyn Error>> {
+    let row = sqlx::query(
+        "SELECT COUNT(*) as count FROM sqlite_master WHERE type = 'table' AND name = $1",
+    )
+    .bind(table)
+    .fetch_one(pool)
+    .await?;
+
+    let count: i64 = row.get("count");
+    Ok(count > 0)
+}
+
+async fn column_exists(pool: &AnyPool, table: &str, column: &str) -> Result<bool, Box<dyn Error>> {
+    let row = sqlx::query("SELECT COUNT(*) as count FROM pragma_table_info($1) WHERE name = $2")
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool)
+        .await?;
+
+    let count: i64 = row.get("count");
+    Ok(count > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::any::AnyPoolOptions;
+    use tempfile::TempDir;
+
+    async fn pool(dir: &TempDir) -> AnyPool {
+        sqlx::any::install_default_drivers();
+        let url = format!(
+            "sqlite:{}?mode=rwc",
+            dir.path().join("commits.db").display()
+        );
+        AnyPoolOptions::new().connect(&url).await.unwrap()
+    }
+
+    async fn count(pool: &AnyPool, sql: &str) -> i64 {
+        sqlx::query(sql).fetch_one(pool).await.unwrap().get(0)
+    }
+
+    async fn create_version_table(pool: &AnyPool) {
+        sqlx::query(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, \
+             description TEXT NOT NULL, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    /// The only table the tracker created before it tracked schema versions.
+    const BASELINE_SCHEMA: &str = r#"
+        CREATE TABLE sent_commits (
+            id INTEGER PRIMARY KEY,
+            commit_id INTEGER NOT NULL UNIQUE,
+            author TEXT NOT NULL,
+            message TEXT NOT NULL,
+            branch TEXT NOT NULL,
+            changeset TEXT NOT NULL,
+            sent_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        INSERT INTO sent_commits (commit_id, author, message, branch, changeset)
+        VALUES (41, 'Alistair', 'Fixed a thing', 'main', 'abc');
+    "#;
+
+    #[tokio::test]
+    async fn migrates_a_fresh_database_to_the_latest_version() {
+        let dir = TempDir::new().unwrap();
+        let pool = pool(&dir).await;
+
+        run(&pool, Backend::Sqlite).await.unwrap();
+
+        let latest = latest_version(Backend::Sqlite);
+        assert_eq!(current_version(&pool).await.unwrap(), latest);
+        assert_eq!(
+            count(&pool, "SELECT COUNT(*) FROM schema_version").await,
+            SQLITE_MIGRATIONS.len() as i64
+        );
+
+        // Running again changes nothing
+        run(&pool, Backend::Sqlite).await.unwrap();
+        assert_eq!(current_version(&pool).await.unwrap(), latest);
+    }
+
+    #[tokio::test]
+    async fn migrates_a_baseline_database_keeping_its_commits() {
+        let dir = TempDir::new().unwrap();
+        let pool = pool(&dir).await;
+        sqlx::raw_sql(BASELINE_SCHEMA).execute(&pool).await.unwrap();
+
+        run(&pool, Backend::Sqlite).await.unwrap();
+
+        assert_eq!(
+            current_version(&pool).await.unwrap(),
+            latest_version(Backend::Sqlite)
+        );
+        let row = sqlx::query(
+            "SELECT c.author, c.message_key, d.status FROM commits c \
+             JOIN deliveries d ON d.commit_id = c.commit_id WHERE c.commit_id = 41",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(row.get::<String, _>("author"), "Alistair");
+        assert_eq!(row.get::<String, _>("message_key"), "");
+        assert_eq!(row.get::<String, _>("status"), "sent");
+    }
+
+    #[tokio::test]
+    async fn adopts_unversioned_databases_at_the_version_their_columns_show() {
+        let dir = TempDir::new().unwrap();
+        let pool = pool(&dir).await;
+        sqlx::raw_sql(BASELINE_SCHEMA).execute(&pool).await.unwrap();
+        // What the tracker created just before migrations were introduced
+        sqlx::raw_sql(
+            r#"
+            CREATE TABLE deferred_commits (
+                commit_id INTEGER PRIMARY KEY,
+                payload TEXT NOT NULL,
+                deferred_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            ALTER TABLE sent_commits ADD COLUMN repo TEXT NOT NULL DEFAULT '';
+            ALTER TABLE sent_commits ADD COLUMN likes INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE sent_commits ADD COLUMN dislikes INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE sent_commits ADD COLUMN message_key TEXT;
+            ALTER TABLE sent_commits ADD COLUMN message_id TEXT;
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        create_version_table(&pool).await;
+
+        assert_eq!(adopt_legacy_schema(&pool).await.unwrap(), 4);
+        assert_eq!(current_version(&pool).await.unwrap(), 4);
+        assert_eq!(
+            count(
+                &pool,
+                "SELECT COUNT(*) FROM sqlite_master \
+                 WHERE type = 'index' AND name = 'idx_sent_commits_duplicates'"
+            )
+            .await,
+            1
+        );
+
+        run(&pool, Backend::Sqlite).await.unwrap();
+        assert_eq!(
+            current_version(&pool).await.unwrap(),
+            latest_version(Backend::Sqlite)
+        );
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM commits").await, 1);
+    }
+
+    #[tokio::test]
+    async fn refuses_databases_from_a_newer_version() {
+        let dir = TempDir::new().unwrap();
+        let pool = pool(&dir).await;
+        run(&pool, Backend::Sqlite).await.unwrap();
+
+        let newer = latest_version(Backend::Sqlite) + 1;
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, 'future')")
+            .bind(newer)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let error = run(&pool, Backend::Sqlite).await.unwrap_err().to_string();
+        assert!(
+            error.contains(&format!("version {} is newer", newer)),
+            "{}",
+            error
+        );
+    }
+}
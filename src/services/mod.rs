@@ -1,7 +1,15 @@
+pub mod archive;
+pub mod backup;
 pub mod database;
 pub mod discord;
+pub mod export;
+pub mod gaps;
+pub mod mentions;
+pub mod migrations;
 pub mod scraper;
 
 pub use database::Database;
 pub use discord::*;
+pub use export::ExportFormat;
+pub use mentions::{MentionMatcher, Mentions};
 pub use scraper::{CommitResult, CommitScraper};
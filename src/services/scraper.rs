@@ -1,27 +1,151 @@
 use crate::models::{CommitInfo, CommitsResponse};
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
 
+/// How long a feed request may take before it's abandoned.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The latest response for one URL. Locked while it's being fetched, so
+/// callers wanting the same feed at the same time wait for that request
+/// instead of making their own.
+type Slot = Arc<tokio::sync::Mutex<Option<(Instant, CommitsResponse)>>>;
+
+/// Fetches the commit feed. Clones share one HTTP client and, when
+/// `sharing_responses` is set, recent responses, so trackers watching the
+/// same feed make one request per poll between them.
+#[derive(Clone)]
 pub struct CommitScraper {
     client: reqwest::Client,
+    share_for: Duration,
+    recent: Arc<Mutex<HashMap<String, Slot>>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommitResult {
     pub commit: CommitInfo,
-    pub total_commits: u32,
-    pub position: u32, // Position in the list (1 = latest)
+    pub total_commits: u32, // 0 when unknown, e.g. for replayed commits
+    pub position: u32,      // Position in the list (1 = latest)
 }
 
-impl CommitScraper {
-    pub fn new() -> Self {
+impl CommitResult {
+    /// A commit loaded from the database. Its feed position isn't stored, so
+    /// posts omit it.
+    pub fn stored(commit: CommitInfo) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            commit,
+            total_commits: 0,
+            position: 0,
         }
     }
+}
+
+impl CommitScraper {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()?,
+            share_for: Duration::ZERO,
+            recent: Arc::default(),
+        })
+    }
+
+    /// Reuses a response for the same URL fetched less than `window` ago.
+    pub fn sharing_responses(mut self, window: Duration) -> Self {
+        self.share_for = window;
+        self
+    }
+
+    /// The HTTP client, for other services to share.
+    pub fn client(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    pub async fn fetch_commits(&self, url: &str) -> Result<CommitsResponse, Box<dyn Error>> {
+        if self.share_for.is_zero() {
+            let response = self.client.get(url).send().await?.error_for_status()?;
+            return Ok(response.json().await?);
+        }
+
+        let slot = self.slot(url);
+        let mut recent = slot.lock().await;
+        if let Some((fetched_at, response)) = recent.as_ref()
+            && fetched_at.elapsed() < self.share_for
+        {
+            return Ok(response.clone());
+        }
+
+        let response: CommitsResponse = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        *recent = Some((Instant::now(), response.clone()));
+        Ok(response)
+    }
+
+    /// The slot for `url`, dropping slots nobody uses that hold nothing
+    /// worth sharing any more. The map is only locked briefly; requests
+    /// wait on their own URL's slot.
+    fn slot(&self, url: &str) -> Slot {
+        let mut slots = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        slots.retain(|_, slot| {
+            Arc::strong_count(slot) > 1
+                || slot.try_lock().is_ok_and(|recent| {
+                    recent
+                        .as_ref()
+                        .is_some_and(|(fetched_at, _)| fetched_at.elapsed() < self.share_for)
+                })
+        });
+        slots.entry(url.to_string()).or_default().clone()
+    }
+
+    /// Fetches the latest page of commits, newest first.
+    pub async fn fetch_latest_commits(
+        &self,
+        url: &str,
+    ) -> Result<Vec<CommitResult>, Box<dyn Error>> {
+        let commits_response = self.fetch_commits(url).await?;
+        Ok(Self::results(commits_response))
+    }
+
+    /// Fetches an older page of the feed, newest first. Page 1 is the latest.
+    pub async fn fetch_page(
+        &self,
+        url: &str,
+        page: u32,
+    ) -> Result<Vec<CommitResult>, Box<dyn Error>> {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let commits_response = self
+            .fetch_commits(&format!("{}{}p={}", url, separator, page))
+            .await?;
+        Ok(Self::results(commits_response))
+    }
+
+    fn results(commits_response: CommitsResponse) -> Vec<CommitResult> {
+        let total_commits = commits_response.total;
+        let skip = commits_response.skip;
+
+        commits_response
+            .results
+            .into_iter()
+            .enumerate()
+            .map(|(index, commit)| CommitResult {
+                commit,
+                total_commits,
+                position: skip + index as u32 + 1,
+            })
+            .collect()
+    }
 
     pub async fn fetch_latest_commit(&self, url: &str) -> Result<CommitResult, Box<dyn Error>> {
-        let response = self.client.get(url).send().await?;
-        let commits_response: CommitsResponse = response.json().await?;
+        let commits_response = self.fetch_commits(url).await?;
 
         let commit = commits_response
             .results
@@ -37,8 +161,107 @@ impl CommitScraper {
     }
 }
 
-impl Default for CommitScraper {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Serves an empty feed at `route`, answering after `delay`.
+    async fn serve_feed(server: &MockServer, route: &str, delay: Duration) {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "total": 0, "skip": 0, "take": 0, "results": [] }))
+                    .set_delay(delay),
+            )
+            .mount(server)
+            .await;
+    }
+
+    fn requests_to(requests: &[wiremock::Request], route: &str) -> usize {
+        requests.iter().filter(|r| r.url.path() == route).count()
+    }
+
+    #[tokio::test]
+    async fn shares_one_request_between_callers() {
+        let server = MockServer::start().await;
+        serve_feed(&server, "/feed", Duration::from_millis(200)).await;
+        let scraper = CommitScraper::new()
+            .unwrap()
+            .sharing_responses(Duration::from_secs(60));
+        let url = format!("{}/feed", server.uri());
+
+        let (first, second) =
+            tokio::join!(scraper.fetch_commits(&url), scraper.fetch_commits(&url));
+        first.unwrap();
+        second.unwrap();
+        scraper.fetch_commits(&url).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests_to(&requests, "/feed"), 1);
+    }
+
+    #[tokio::test]
+    async fn fetches_other_feeds_while_one_is_slow() {
+        let server = MockServer::start().await;
+        serve_feed(&server, "/slow", Duration::from_secs(5)).await;
+        serve_feed(&server, "/fast", Duration::ZERO).await;
+        let scraper = CommitScraper::new()
+            .unwrap()
+            .sharing_responses(Duration::from_secs(60));
+
+        let slow = tokio::spawn({
+            let scraper = scraper.clone();
+            let url = format!("{}/slow", server.uri());
+            async move {
+                scraper
+                    .fetch_commits(&url)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let fast = tokio::time::timeout(
+            Duration::from_secs(2),
+            scraper.fetch_commits(&format!("{}/fast", server.uri())),
+        )
+        .await;
+        assert!(fast.expect("waited for the slow feed").is_ok());
+        slow.abort();
+    }
+
+    #[tokio::test]
+    async fn fails_on_error_responses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/feed"))
+            .respond_with(ResponseTemplate::new(503).set_body_json(json!({ "results": [] })))
+            .mount(&server)
+            .await;
+        let url = format!("{}/feed", server.uri());
+
+        let error = CommitScraper::new()
+            .unwrap()
+            .fetch_commits(&url)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("503"), "{}", error);
+
+        // Failures aren't shared, so the next poll tries again
+        let scraper = CommitScraper::new()
+            .unwrap()
+            .sharing_responses(Duration::from_secs(60));
+        for _ in 0..2 {
+            let error = scraper.fetch_commits(&url).await.unwrap_err().to_string();
+            assert!(error.contains("503"), "{}", error);
+        }
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests_to(&requests, "/feed"), 3);
     }
 }
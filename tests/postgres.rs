@@ -0,0 +1,320 @@
+//! Runs the storage layer against a real PostgreSQL server. Skipped unless
+//! `DATABASE_URL` points at one, e.g.
+//!
+//! ```sh
+//! DATABASE_URL=postgres://postgres@localhost/tracker_test cargo test --features postgres
+//! ```
+//!
+//! The database is wiped first, so never point this at one you care about.
+#![cfg(feature = "postgres")]
+
+use chrono::{Duration, Utc};
+use rust_commit_tracker::models::{
+    CommitFilter, CommitInfo, CommitRecord, DigestPeriod, SearchQuery,
+};
+use rust_commit_tracker::services::{Database, migrations};
+use serde_json::json;
+use sqlx::{AnyPool, Row};
+use std::time::Duration as StdDuration;
+use tokio::sync::Mutex;
+
+const DESTINATION: &str = "discord";
+
+/// Every test wipes the same database, so they take turns
+static DATABASE: Mutex<()> = Mutex::const_new(());
+
+fn database_url() -> Option<String> {
+    std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
+}
+
+fn commit(id: i32, author: &str, message: &str) -> CommitInfo {
+    serde_json::from_value(json!({
+        "id": id,
+        "repo": "rust_reboot",
+        "branch": "main",
+        "changeset": format!("c{}", id),
+        "created": "2025-01-01T12:00:00",
+        "likes": id,
+        "dislikes": 0,
+        "message": message,
+        "user": { "name": author, "avatar": format!("https://example.com/{}.png", author) },
+    }))
+    .unwrap()
+}
+
+async fn claim(database: &Database, commit_id: i32, owner: &str) -> bool {
+    database
+        .claim_commit(commit_id, DESTINATION, owner, StdDuration::from_secs(60))
+        .await
+        .unwrap()
+}
+
+async fn reset(url: &str) {
+    sqlx::any::install_default_drivers();
+    let pool = AnyPool::connect(url).await.unwrap();
+    sqlx::raw_sql("DROP SCHEMA public CASCADE; CREATE SCHEMA public;")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+}
+
+#[tokio::test]
+async fn stores_claims_and_searches_commits() {
+    let Some(url) = database_url() else {
+        eprintln!("DATABASE_URL is not a PostgreSQL URL, skipping");
+        return;
+    };
+    let _turn = DATABASE.lock().await;
+    reset(&url).await;
+
+    // Migrations run on a fresh database and are a no-op the second time
+    let database = Database::new(&url).await.unwrap();
+    let latest = migrations::POSTGRES_MIGRATIONS.last().unwrap().version;
+    assert_eq!(database.schema_version().await.unwrap(), latest);
+    let database = Database::new(&url).await.unwrap();
+    assert_eq!(database.schema_version().await.unwrap(), latest);
+
+    // Timestamps are real timestamps, not text
+    let pool = AnyPool::connect(&url).await.unwrap();
+    let text_columns: Vec<String> = sqlx::query(
+        "SELECT table_name || '.' || column_name AS name FROM information_schema.columns \
+         WHERE table_schema = 'public' AND column_name LIKE '%\\_at' AND data_type = 'text' \
+         ORDER BY name",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| row.get("name"))
+    .collect();
+    assert!(
+        text_columns.is_empty(),
+        "text timestamps: {:?}",
+        text_columns
+    );
+
+    // Inserting
+    assert!(
+        database
+            .record_commit(&commit(1, "alice", "Fix the door"))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !database
+            .record_commit(&commit(2, "alice", "Add a window"))
+            .await
+            .unwrap()
+    );
+    assert!(
+        database
+            .record_commit(&commit(3, "bob", "Fix the door"))
+            .await
+            .unwrap()
+    );
+    assert_eq!(database.get_sent_commits_count().await.unwrap(), 3);
+    assert_eq!(database.get_latest_commit_id().await.unwrap(), Some(3));
+
+    let authors = database.get_authors(10).await.unwrap();
+    assert_eq!(authors[0].name, "alice");
+    assert_eq!(authors[0].commit_count, 2);
+    let profile = database.get_author_profile("alice").await.unwrap().unwrap();
+    assert_eq!(profile.repos[0].commit_count, 2);
+    assert_eq!(profile.avatars.len(), 1);
+
+    // Claiming: one instance at a time, until the commit is posted
+    assert!(claim(&database, 1, "a").await);
+    assert!(claim(&database, 1, "a").await);
+    assert!(!claim(&database, 1, "b").await);
+    assert!(!database.is_commit_sent(1, DESTINATION).await.unwrap());
+
+    database
+        .mark_commit_sent(1, DESTINATION, Some("m1"), Some(200))
+        .await
+        .unwrap();
+    assert!(database.is_commit_sent(1, DESTINATION).await.unwrap());
+    assert!(!claim(&database, 1, "a").await);
+
+    // Abandoned claims and failures come back for a retry
+    assert!(claim(&database, 2, "a").await);
+    database
+        .record_delivery_failure(3, DESTINATION, Some(500), "boom")
+        .await
+        .unwrap();
+    let undelivered = database
+        .get_undelivered_commits(DESTINATION, 5, Utc::now() + Duration::seconds(1))
+        .await
+        .unwrap();
+    let ids: Vec<i32> = undelivered.iter().map(|c| c.id).collect();
+    assert_eq!(ids, vec![2, 3]);
+
+    // Bursts keep their due time
+    let deliver_at = Utc::now() + Duration::minutes(5);
+    database
+        .mark_commit_pending(3, DESTINATION, deliver_at)
+        .await
+        .unwrap();
+    let pending = database.get_pending_commits(DESTINATION).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].0.id, 3);
+    assert_eq!(pending[0].1.timestamp(), deliver_at.timestamp());
+
+    // Duplicates of the posted commit
+    let (message_id, branches) = database
+        .find_recent_duplicate(&commit(4, "alice", "Fix the door"), DESTINATION, 60)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message_id, "m1");
+    assert_eq!(branches[0].commit_id, 1);
+
+    // Searching, with and without filters
+    let hits = database
+        .search_commits(&SearchQuery {
+            text: "door".to_string(),
+            filter: CommitFilter::default(),
+            limit: 10,
+        })
+        .await
+        .unwrap();
+    let ids: Vec<i32> = hits.iter().map(|hit| hit.commit_id).collect();
+    assert_eq!(ids, vec![3, 1]);
+
+    let today = Utc::now().date_naive();
+    let hits = database
+        .search_commits(&SearchQuery {
+            text: "fix".to_string(),
+            filter: CommitFilter {
+                author: Some("bob".to_string()),
+                until: Some(today),
+                ..Default::default()
+            },
+            limit: 10,
+        })
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].created, "2025-01-01T12:00:00");
+
+    // Reports over first-seen times
+    let report = database
+        .get_digest_report(DigestPeriod::Daily, Utc::now() + Duration::minutes(1), 5, 5)
+        .await
+        .unwrap();
+    assert_eq!(report.total_commits, 3);
+
+    // Leases and state
+    let ttl = StdDuration::from_secs(60);
+    assert!(database.acquire_lease("poller", "a", ttl).await.unwrap());
+    assert!(!database.acquire_lease("poller", "b", ttl).await.unwrap());
+    assert_eq!(
+        database.lease_holder("poller").await.unwrap().as_deref(),
+        Some("a")
+    );
+    database.release_lease("poller", "a").await.unwrap();
+    assert!(database.acquire_lease("poller", "b", ttl).await.unwrap());
+
+    database.set_state("digest", "sent").await.unwrap();
+    assert_eq!(
+        database.get_state("digest").await.unwrap().as_deref(),
+        Some("sent")
+    );
+
+    // Exported records round-trip through an import
+    let records: Vec<CommitRecord> = database
+        .export_commits(&CommitFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 3);
+    let sent = &records[0].deliveries[0];
+    assert_eq!(sent.status, "sent");
+    assert!(sent.delivered_at.is_some());
+    assert_eq!(records[0].first_seen_at.len(), "2025-01-01 12:00:00".len());
+
+    assert_eq!(database.delete_commits(&[1, 2, 3]).await.unwrap(), 3);
+    let summary = database.import_commits(&records).await.unwrap();
+    assert_eq!(summary.commits, 3);
+    assert_eq!(summary.deliveries, 3);
+    let reimported = database.get_commit_records(&[1]).await.unwrap();
+    assert_eq!(reimported[0].first_seen_at, records[0].first_seen_at);
+    assert_eq!(
+        reimported[0].deliveries[0].delivered_at,
+        records[0].deliveries[0].delivered_at
+    );
+}
+
+#[tokio::test]
+async fn converts_text_timestamps_when_upgrading() {
+    let Some(url) = database_url() else {
+        eprintln!("DATABASE_URL is not a PostgreSQL URL, skipping");
+        return;
+    };
+    let _turn = DATABASE.lock().await;
+    reset(&url).await;
+
+    // A database left at version 10, when timestamps were still text
+    let pool = AnyPool::connect(&url).await.unwrap();
+    sqlx::raw_sql(
+        "CREATE TABLE schema_version (
+            version BIGINT PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TIMESTAMPTZ DEFAULT now()
+        )",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    for migration in migrations::POSTGRES_MIGRATIONS
+        .iter()
+        .filter(|m| m.version <= 10)
+    {
+        sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    let raw_json = serde_json::to_string(&commit(1, "alice", "Fix the door")).unwrap();
+    sqlx::raw_sql(&format!(
+        "INSERT INTO commits (commit_id, repo, branch, changeset, created, message, \
+            message_key, author, avatar, raw_json, first_seen_at) \
+         VALUES (1, 'rust_reboot', 'main', 'c1', '', 'Fix the door', 'fix the door', \
+            'alice', '', '{}', '2025-01-01 12:00:00'); \
+         INSERT INTO deliveries (commit_id, destination, status, attempts, message_id, \
+            created_at, updated_at, delivered_at) \
+         VALUES (1, 'discord', 'sent', 1, 'm1', '2025-01-01 12:00:00', \
+            '2025-01-01 12:00:05', '2025-01-01 12:00:05');",
+        raw_json
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let database = Database::new(&url).await.unwrap();
+    assert_eq!(database.schema_version().await.unwrap(), 11);
+
+    let records = database.get_commit_records(&[1]).await.unwrap();
+    assert_eq!(records[0].first_seen_at, "2025-01-01 12:00:00");
+    let delivery = &records[0].deliveries[0];
+    assert_eq!(delivery.created_at, "2025-01-01 12:00:00");
+    assert_eq!(
+        delivery.delivered_at.as_deref(),
+        Some("2025-01-01 12:00:05")
+    );
+
+    // Commits without a feed time fall back to when they were first seen
+    let hits = database
+        .search_commits(&SearchQuery {
+            text: "door".to_string(),
+            limit: 10,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(hits[0].created, "2025-01-01 12:00:00");
+}